@@ -0,0 +1,282 @@
+//! `#[derive(SceneParams)]` for the scenes of the demo.
+//!
+//! On a struct with named fields, the derive implements `Default`, `Display`, `serde::Serialize`,
+//! `serde::Deserialize` and `crate::simulation_scene::SceneParams`, which shows a widget per field
+//! and saves the parameters as RON.
+//!
+//! ```ignore
+//! #[derive(Clone, SceneParams)]
+//! #[scene(name = "Figure8")]
+//! pub struct Figure8 {
+//!     #[param(default = 30.0, range = 5.0..=100.0, log, integer)]
+//!     radius: f32,
+//!     #[param(default = 1E5, hidden)]
+//!     mass: f32,
+//! }
+//! ```
+//!
+//! Struct attributes:
+//! - `name = "..."`: what `Display` writes, the name of the struct by default.
+//!
+//! Field attributes:
+//! - `default = expr`: the default value, `Default::default()` otherwise.
+//! - `range = start..=end`: shows a slider instead of a drag value. The bounds can read `self`.
+//! - `log`, `integer`: logarithmic and integer sliders.
+//! - `label = "..."`: text next to the widget, the field name in sentence case by default.
+//! - `section = "..."`: starts a new section with this title before the field.
+//! - `enabled = expr`: greys the widget out when false. Can read `self`.
+//! - `widget = path`: shows the field with `path(&mut Ui, &mut T)` instead of the default widget.
+//! - `hidden`: saved and reset, but without a widget.
+//!
+//! `bool` fields are shown as checkboxes.
+
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{parse_macro_input, spanned::Spanned, Data, DeriveInput, Expr, Field, Fields, LitStr};
+
+#[proc_macro_derive(SceneParams, attributes(scene, param))]
+pub fn derive_scene_params(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+#[derive(Default)]
+struct Param {
+    default: Option<Expr>,
+    range: Option<Expr>,
+    log: bool,
+    integer: bool,
+    label: Option<LitStr>,
+    section: Option<LitStr>,
+    enabled: Option<Expr>,
+    widget: Option<syn::Path>,
+    hidden: bool,
+}
+
+impl Param {
+    fn parse(field: &Field) -> syn::Result<Self> {
+        let mut param = Param::default();
+        for attr in field.attrs.iter().filter(|a| a.path().is_ident("param")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("default") {
+                    param.default = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("range") {
+                    param.range = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("log") {
+                    param.log = true;
+                } else if meta.path.is_ident("integer") {
+                    param.integer = true;
+                } else if meta.path.is_ident("label") {
+                    param.label = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("section") {
+                    param.section = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("enabled") {
+                    param.enabled = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("widget") {
+                    param.widget = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("hidden") {
+                    param.hidden = true;
+                } else {
+                    return Err(meta.error("unknown parameter attribute"));
+                }
+                Ok(())
+            })?;
+        }
+        Ok(param)
+    }
+}
+
+fn scene_name(input: &DeriveInput) -> syn::Result<String> {
+    let mut name = input.ident.to_string();
+    for attr in input.attrs.iter().filter(|a| a.path().is_ident("scene")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("name") {
+                name = meta.value()?.parse::<LitStr>()?.value();
+                Ok(())
+            } else {
+                Err(meta.error("unknown scene attribute"))
+            }
+        })?;
+    }
+    Ok(name)
+}
+
+/// `bodies_max_mass` becomes "Bodies max mass".
+fn sentence_case(ident: &str) -> String {
+    let text = ident.trim_start_matches('_').replace('_', " ");
+    let mut chars = text.chars();
+    chars
+        .next()
+        .map(|first| first.to_uppercase().chain(chars).collect())
+        .unwrap_or_default()
+}
+
+fn is_bool(field: &Field) -> bool {
+    matches!(&field.ty, syn::Type::Path(ty) if ty.path.is_ident("bool"))
+}
+
+fn widget(field: &Field, param: &Param) -> TokenStream {
+    if param.hidden {
+        return TokenStream::new();
+    }
+
+    let ident = field.ident.as_ref().unwrap();
+    let label = param
+        .label
+        .as_ref()
+        .map_or_else(|| sentence_case(&ident.to_string()), LitStr::value);
+    let section = param.section.as_ref().map(|section| {
+        quote! {
+            ui.separator();
+            ui.label(#section);
+        }
+    });
+    let enabled = param
+        .enabled
+        .as_ref()
+        .map_or_else(|| quote!(true), |enabled| quote!(#enabled));
+
+    let add = if let Some(widget) = &param.widget {
+        quote! {
+            ui.add_enabled_ui(enabled, |ui| #widget(ui, &mut self.#ident));
+        }
+    } else if is_bool(field) {
+        quote! {
+            ui.add_enabled(enabled, egui::Checkbox::new(&mut self.#ident, #label));
+        }
+    } else if let Some(range) = &param.range {
+        let log = param.log.then(|| quote!(.logarithmic(true)));
+        let integer = param.integer.then(|| quote!(.integer()));
+        quote! {
+            let range = #range;
+            ui.add_enabled(
+                enabled,
+                egui::Slider::new(&mut self.#ident, range).text(#label) #log #integer,
+            );
+        }
+    } else {
+        quote! {
+            ui.horizontal(|ui| {
+                ui.add_enabled(enabled, egui::DragValue::new(&mut self.#ident));
+                ui.label(#label);
+            });
+        }
+    };
+
+    quote! {
+        #section
+        {
+            let enabled = #enabled;
+            #add
+        }
+    }
+}
+
+fn expand(input: DeriveInput) -> syn::Result<TokenStream> {
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => return Err(syn::Error::new(input.span(), "expected named fields")),
+        },
+        _ => return Err(syn::Error::new(input.span(), "expected a struct")),
+    };
+    if !input.generics.params.is_empty() {
+        return Err(syn::Error::new(
+            input.generics.span(),
+            "generic scenes are not supported",
+        ));
+    }
+
+    let ident = &input.ident;
+    let name = scene_name(&input)?;
+    let params = fields
+        .iter()
+        .map(Param::parse)
+        .collect::<syn::Result<Vec<_>>>()?;
+
+    let idents: Vec<_> = fields.iter().map(|f| f.ident.as_ref().unwrap()).collect();
+    let types: Vec<_> = fields.iter().map(|f| &f.ty).collect();
+    let defaults = params.iter().map(|param| {
+        param.default.as_ref().map_or_else(
+            || quote!(::core::default::Default::default()),
+            |default| quote!(#default),
+        )
+    });
+    let widgets = fields.iter().zip(&params).map(|(f, p)| widget(f, p));
+
+    let ser = format_ident!("{}Ser", ident);
+    let de = format_ident!("{}De", ident);
+
+    Ok(quote! {
+        impl ::core::default::Default for #ident {
+            fn default() -> Self {
+                Self {
+                    #(#idents: #defaults,)*
+                }
+            }
+        }
+
+        impl ::core::fmt::Display for #ident {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                f.write_str(#name)
+            }
+        }
+
+        const _: () = {
+            #[derive(::serde::Serialize)]
+            #[serde(rename = #name)]
+            struct #ser<'a> {
+                #(#idents: &'a #types,)*
+            }
+
+            // Missing fields take their default, so that older parameters still load.
+            #[derive(::serde::Deserialize)]
+            #[serde(rename = #name, default)]
+            struct #de {
+                #(#idents: #types,)*
+            }
+
+            impl ::core::default::Default for #de {
+                fn default() -> Self {
+                    let #ident { #(#idents,)* } = ::core::default::Default::default();
+                    Self { #(#idents,)* }
+                }
+            }
+
+            impl ::serde::Serialize for #ident {
+                fn serialize<S: ::serde::Serializer>(&self, serializer: S) -> ::core::result::Result<S::Ok, S::Error> {
+                    ::serde::Serialize::serialize(&#ser { #(#idents: &self.#idents,)* }, serializer)
+                }
+            }
+
+            impl<'de> ::serde::Deserialize<'de> for #ident {
+                fn deserialize<D: ::serde::Deserializer<'de>>(deserializer: D) -> ::core::result::Result<Self, D::Error> {
+                    let #de { #(#idents,)* } = ::serde::Deserialize::deserialize(deserializer)?;
+                    ::core::result::Result::Ok(Self { #(#idents,)* })
+                }
+            }
+        };
+
+        impl crate::simulation_scene::SceneParams for #ident {
+            fn show_ui(&mut self, ui: &mut ::bevy_egui::egui::Ui) {
+                use ::bevy_egui::egui;
+                #(#widgets)*
+            }
+
+            fn parameters(&self) -> ::core::option::Option<::std::string::String> {
+                ::ron::to_string(self).ok()
+            }
+
+            fn set_parameters(&mut self, parameters: &str) -> ::ron::Result<()> {
+                *self = ::ron::from_str(parameters)?;
+                ::core::result::Result::Ok(())
+            }
+
+            fn reset(&mut self) {
+                *self = ::core::default::Default::default();
+            }
+        }
+    })
+}
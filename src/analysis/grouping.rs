@@ -0,0 +1,136 @@
+use std::collections::HashMap;
+
+use bevy::math::Vec3;
+
+use crate::G;
+
+#[derive(Clone, Copy)]
+pub struct GroupBody {
+    pub position: Vec3,
+    pub velocity: Vec3,
+    pub mass: f32,
+}
+
+/// Binding energy of a pair, negative when the two bodies are bound to each other.
+pub fn binding_energy(a: GroupBody, b: GroupBody) -> f32 {
+    let total_mass = a.mass + b.mass;
+    if total_mass <= 0.0 {
+        return f32::INFINITY;
+    }
+    let distance = a.position.distance(b.position);
+    if distance <= 0.0 {
+        return f32::INFINITY;
+    }
+
+    let reduced_mass = a.mass * b.mass / total_mass;
+    reduced_mass * (0.5 * a.velocity.distance_squared(b.velocity) - G * total_mass / distance)
+}
+
+/// Finds the pairs of massive bodies that are bound and are each other's most bound partner.
+pub fn binaries(bodies: &[GroupBody]) -> Vec<(usize, usize)> {
+    let most_bound: Vec<_> = (0..bodies.len())
+        .map(|i| {
+            (0..bodies.len())
+                .filter(|&j| j != i)
+                .map(|j| (j, binding_energy(bodies[i], bodies[j])))
+                .filter(|(_, energy)| *energy < 0.0)
+                .min_by(|(_, a), (_, b)| a.total_cmp(b))
+                .map(|(j, _)| j)
+        })
+        .collect();
+
+    most_bound
+        .iter()
+        .enumerate()
+        .filter_map(|(i, partner)| {
+            let j = (*partner)?;
+            (i < j && most_bound[j] == Some(i)).then_some((i, j))
+        })
+        .collect()
+}
+
+/// Groups bodies closer than `linking_length` to any other member, transitively.
+///
+/// Returns the groups of at least two bodies, each as a list of indices.
+pub fn friends_of_friends(positions: &[Vec3], linking_length: f32) -> Vec<Vec<usize>> {
+    let mut parents: Vec<_> = (0..positions.len()).collect();
+    fn root(parents: &mut [usize], mut index: usize) -> usize {
+        while parents[index] != index {
+            parents[index] = parents[parents[index]];
+            index = parents[index];
+        }
+        index
+    }
+
+    // Bucket the bodies in cells of the linking length so that only neighbouring cells are compared.
+    let cell = |position: Vec3| {
+        let cell = (position / linking_length).floor();
+        (cell.x as i32, cell.y as i32)
+    };
+    let mut grid: HashMap<(i32, i32), Vec<usize>> = HashMap::new();
+    for (index, position) in positions.iter().enumerate() {
+        grid.entry(cell(*position)).or_default().push(index);
+    }
+
+    let linking_squared = linking_length * linking_length;
+    for (index, position) in positions.iter().enumerate() {
+        let (x, y) = cell(*position);
+        for neighbour in (x - 1..=x + 1).flat_map(|x| (y - 1..=y + 1).map(move |y| (x, y))) {
+            for &other in grid.get(&neighbour).into_iter().flatten() {
+                if other > index && position.distance_squared(positions[other]) <= linking_squared {
+                    let (a, b) = (root(&mut parents, index), root(&mut parents, other));
+                    parents[a.max(b)] = a.min(b);
+                }
+            }
+        }
+    }
+
+    let mut groups: HashMap<usize, Vec<usize>> = HashMap::new();
+    for index in 0..positions.len() {
+        let root = root(&mut parents, index);
+        groups.entry(root).or_default().push(index);
+    }
+
+    let mut groups: Vec<_> = groups.into_values().filter(|g| g.len() > 1).collect();
+    groups.sort_by_key(|group| group[0]);
+    groups
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_mutually_bound_pairs_are_binaries() {
+        let body = |x: f32, vy: f32, mass: f32| GroupBody {
+            position: Vec3::new(x, 0.0, 0.0),
+            velocity: Vec3::new(0.0, vy, 0.0),
+            mass,
+        };
+        // A tight binary, a distant body bound to it, and a fast flyby.
+        let bodies = [
+            body(0.0, -1.0, 10.0),
+            body(10.0, 1.0, 10.0),
+            body(300.0, 0.0, 5.0),
+            body(-20.0, 500.0, 5.0),
+        ];
+
+        assert_eq!(binaries(&bodies), vec![(0, 1)]);
+    }
+
+    #[test]
+    fn friends_of_friends_links_chains() {
+        let positions = [
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(9.0, 0.0, 0.0),
+            Vec3::new(18.0, 0.0, 0.0),
+            Vec3::new(100.0, 0.0, 0.0),
+            Vec3::new(-50.0, -50.0, 0.0),
+            Vec3::new(-50.0, -45.0, 0.0),
+        ];
+
+        let groups = friends_of_friends(&positions, 10.0);
+
+        assert_eq!(groups, vec![vec![0, 1, 2], vec![4, 5]]);
+    }
+}
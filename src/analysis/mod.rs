@@ -0,0 +1,54 @@
+mod grouping;
+mod systems;
+
+use bevy::{prelude::*, time::FixedTimestep};
+
+pub struct AnalysisPlugin;
+
+impl Plugin for AnalysisPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GroupAnalysis>()
+            .init_resource::<Groups>()
+            .add_system(systems::analysis_window)
+            .add_system(systems::detect_groups.with_run_criteria(FixedTimestep::step(1.0)))
+            .add_system(systems::recolor_clusters.after(systems::detect_groups));
+    }
+}
+
+pub struct GroupAnalysis {
+    pub enabled: bool,
+    /// Bodies closer than this are linked into the same cluster.
+    pub linking_length: f32,
+    pub recolor: bool,
+}
+
+impl Default for GroupAnalysis {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            linking_length: 30.0,
+            recolor: false,
+        }
+    }
+}
+
+pub struct ClusterInfo {
+    pub id: usize,
+    pub members: usize,
+    pub mass: f32,
+}
+
+/// Result of the last analysis, clusters sorted by decreasing total mass.
+#[derive(Default)]
+pub struct Groups {
+    pub clusters: Vec<ClusterInfo>,
+    pub binaries: Vec<(Entity, Entity)>,
+}
+
+/// Friends-of-friends cluster a body belongs to, ids are ordered by decreasing total mass.
+#[derive(Component, Clone, Copy, PartialEq, Eq)]
+pub struct Cluster(pub usize);
+
+/// Colour of a body before it was recoloured by its cluster.
+#[derive(Component)]
+pub struct OriginalColor(pub Color);
@@ -0,0 +1,179 @@
+use bevy::prelude::*;
+use bevy_egui::{
+    egui::{Grid, ScrollArea, Slider, Window},
+    EguiContext,
+};
+use heron::Velocity;
+
+use super::{
+    grouping::{binaries, friends_of_friends, GroupBody},
+    Cluster, ClusterInfo, GroupAnalysis, Groups, OriginalColor,
+};
+use crate::{nbody::PointMass, selection::body_label};
+
+pub fn analysis_window(
+    mut egui_ctx: ResMut<EguiContext>,
+    mut analysis: ResMut<GroupAnalysis>,
+    groups: Res<Groups>,
+    names: Query<Option<&Name>>,
+) {
+    Window::new("Clusters").show(egui_ctx.ctx_mut(), |ui| {
+        ui.checkbox(&mut analysis.enabled, "Detect binaries and clusters");
+        ui.add(Slider::new(&mut analysis.linking_length, 1.0..=200.0).text("Linking length"));
+        ui.checkbox(&mut analysis.recolor, "Colour by cluster");
+
+        if !analysis.enabled {
+            return;
+        }
+        ui.separator();
+
+        ui.label(format!("Binaries: {}", groups.binaries.len()));
+        ui.label(format!("Clusters: {}", groups.clusters.len()));
+
+        ScrollArea::vertical().max_height(200.0).show(ui, |ui| {
+            Grid::new("clusters")
+                .num_columns(3)
+                .striped(true)
+                .show(ui, |ui| {
+                    ui.label("Cluster");
+                    ui.label("Bodies");
+                    ui.label("Mass");
+                    ui.end_row();
+
+                    for cluster in &groups.clusters {
+                        ui.label(format!("#{}", cluster.id));
+                        ui.label(cluster.members.to_string());
+                        ui.label(format!("{:.1}", cluster.mass));
+                        ui.end_row();
+                    }
+                });
+
+            if !groups.binaries.is_empty() {
+                ui.separator();
+                let label = |e: Entity| body_label(e, names.get(e).ok().flatten());
+                for (a, b) in &groups.binaries {
+                    ui.label(format!("{} & {}", label(*a), label(*b)));
+                }
+            }
+        });
+    });
+}
+
+pub fn detect_groups(
+    mut commands: Commands,
+    analysis: Res<GroupAnalysis>,
+    mut groups: ResMut<Groups>,
+    bodies: Query<(
+        Entity,
+        &GlobalTransform,
+        &Velocity,
+        &PointMass,
+        Option<&Cluster>,
+    )>,
+) {
+    if !analysis.enabled {
+        if analysis.is_changed() {
+            for (entity, .., cluster) in bodies.iter() {
+                if cluster.is_some() {
+                    commands.entity(entity).remove::<Cluster>();
+                }
+            }
+            *groups = Groups::default();
+        }
+        return;
+    }
+
+    let (entities, bodies): (Vec<_>, Vec<_>) = bodies
+        .iter()
+        .map(|(entity, transform, velocity, point_mass, cluster)| {
+            let body = GroupBody {
+                position: transform.translation(),
+                velocity: velocity.linear,
+                mass: point_mass.mass(),
+            };
+            ((entity, cluster.copied()), body)
+        })
+        .unzip();
+
+    let massive: Vec<_> = (0..bodies.len())
+        .filter(|&i| bodies[i].mass > 0.0)
+        .collect();
+    let massive_bodies: Vec<_> = massive.iter().map(|&i| bodies[i]).collect();
+    groups.binaries = binaries(&massive_bodies)
+        .into_iter()
+        .map(|(a, b)| (entities[massive[a]].0, entities[massive[b]].0))
+        .collect();
+
+    let positions: Vec<_> = bodies.iter().map(|body| body.position).collect();
+    let mut clusters: Vec<_> = friends_of_friends(&positions, analysis.linking_length)
+        .into_iter()
+        .map(|members| {
+            let mass = members.iter().map(|&i| bodies[i].mass).sum::<f32>();
+            (members, mass)
+        })
+        .collect();
+    clusters.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+
+    let mut assigned = vec![None; bodies.len()];
+    groups.clusters = clusters
+        .into_iter()
+        .enumerate()
+        .map(|(id, (members, mass))| {
+            for &index in &members {
+                assigned[index] = Some(Cluster(id));
+            }
+            ClusterInfo {
+                id,
+                members: members.len(),
+                mass,
+            }
+        })
+        .collect();
+
+    for ((entity, previous), cluster) in entities.into_iter().zip(assigned) {
+        match cluster {
+            Some(cluster) if previous != Some(cluster) => {
+                commands.entity(entity).insert(cluster);
+            }
+            None if previous.is_some() => {
+                commands.entity(entity).remove::<Cluster>();
+            }
+            _ => {}
+        }
+    }
+}
+
+fn cluster_color(Cluster(id): Cluster) -> Color {
+    // Golden angle hue steps keep neighbouring ids distinct.
+    Color::hsl((id as f32 * 137.5) % 360.0, 0.8, 0.6)
+}
+
+pub fn recolor_clusters(
+    mut commands: Commands,
+    analysis: Res<GroupAnalysis>,
+    mut bodies: Query<(
+        Entity,
+        &mut Sprite,
+        Option<&Cluster>,
+        Option<&OriginalColor>,
+    )>,
+) {
+    for (entity, mut sprite, cluster, original) in bodies.iter_mut() {
+        match (cluster, original) {
+            (Some(cluster), _) if analysis.recolor && analysis.enabled => {
+                if original.is_none() {
+                    commands.entity(entity).insert(OriginalColor(sprite.color));
+                }
+                let color = cluster_color(*cluster);
+                if sprite.color != color {
+                    sprite.color = color;
+                }
+            }
+            (_, Some(OriginalColor(color))) => {
+                sprite.color = *color;
+                commands.entity(entity).remove::<OriginalColor>();
+            }
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,211 @@
+use std::f32::consts::TAU;
+
+use bevy::prelude::Vec2;
+
+/// Initial conditions of a periodic solution, in units where G and the mass of each body are 1.
+pub enum InitialState {
+    /// Bodies at (-1, 0), (1, 0) and the origin, the first two moving at (p1, p2) and the third at
+    /// -2 (p1, p2), as tabulated by Šuvakov and Dmitrašinović.
+    Isosceles { p1: f32, p2: f32 },
+    /// Bodies on the x axis moving along the y axis, as tabulated by Broucke.
+    Collinear { x: [f32; 3], vy: [f32; 3] },
+    /// Positions and velocities of each body.
+    Explicit(&'static [([f32; 2], [f32; 2])]),
+}
+
+pub struct Solution {
+    pub name: &'static str,
+    pub family: &'static str,
+    /// Period in the units of the initial state, if known.
+    pub period: Option<f32>,
+    pub state: InitialState,
+}
+
+impl Solution {
+    /// Positions and velocities of the bodies.
+    pub fn bodies(&self) -> Vec<(Vec2, Vec2)> {
+        match self.state {
+            InitialState::Isosceles { p1, p2 } => {
+                let v = Vec2::new(p1, p2);
+                vec![(-Vec2::X, v), (Vec2::X, v), (Vec2::ZERO, -2.0 * v)]
+            }
+            InitialState::Collinear { x, vy } => x
+                .iter()
+                .zip(vy)
+                .map(|(&x, vy)| (Vec2::new(x, 0.0), Vec2::new(0.0, vy)))
+                .collect(),
+            InitialState::Explicit(bodies) => bodies
+                .iter()
+                .map(|&(p, v)| (Vec2::from(p), Vec2::from(v)))
+                .collect(),
+        }
+    }
+}
+
+const fn suvakov(name: &'static str, p1: f32, p2: f32, period: f32) -> Solution {
+    Solution {
+        name,
+        family: "Šuvakov–Dmitrašinović",
+        period: Some(period),
+        state: InitialState::Isosceles { p1, p2 },
+    }
+}
+
+const fn broucke(name: &'static str, x: [f32; 3], vy: [f32; 3], period: f32) -> Solution {
+    Solution {
+        name,
+        family: "Broucke",
+        period: Some(period),
+        state: InitialState::Collinear { x, vy },
+    }
+}
+
+pub const CATALOGUE: &[Solution] = &[
+    Solution {
+        name: "Figure-eight",
+        family: "Chenciner–Montgomery",
+        period: Some(6.325_914),
+        state: InitialState::Explicit(&[
+            ([-0.970_004_4, 0.243_087_53], [0.466_203_7, 0.432_365_73]),
+            ([0.970_004_4, -0.243_087_53], [0.466_203_7, 0.432_365_73]),
+            ([0.0, 0.0], [-0.932_407_4, -0.864_731_46]),
+        ]),
+    },
+    Solution {
+        name: "Double oval",
+        family: "Other",
+        period: None,
+        state: InitialState::Explicit(&[
+            ([0.486_657_68, 0.755_041_9], [-0.182_709_86, 0.363_013_3]),
+            ([-0.681_738, 0.293_660_22], [-0.579_074_9, -0.748_157_5]),
+            ([-0.022_596_328, -0.612_645_6], [0.761_784_8, 0.385_144_2]),
+        ]),
+    },
+    suvakov("Butterfly I", 0.306_893, 0.125_507, 6.2356),
+    suvakov("Butterfly II", 0.392_955, 0.097_579, 7.0039),
+    suvakov("Butterfly III", 0.405_916, 0.230_163, 13.8658),
+    suvakov("Moth I", 0.464_445, 0.396_06, 14.8939),
+    suvakov("Moth II", 0.439_166, 0.452_968, 28.6703),
+    suvakov("Moth III", 0.383_444, 0.377_364, 25.8406),
+    suvakov("Goggles", 0.0833, 0.127_889, 10.4668),
+    suvakov("Dragonfly", 0.080_584, 0.588_836, 21.271),
+    suvakov("Yin-yang I", 0.513_938, 0.304_736, 17.3284),
+    suvakov("Yin-yang II", 0.416_822, 0.330_333, 55.7898),
+    suvakov("Yarn", 0.559_064, 0.349_192, 55.5018),
+    suvakov("Bumblebee", 0.184_279, 0.587_188, 63.5345),
+    suvakov("Butterfly IV", 0.350_112, 0.079_339, 79.4759),
+    broucke(
+        "Broucke A1",
+        [-0.989_262, 2.209_617_7, -1.220_355_7],
+        [1.916_924_4, 0.191_026_87, -2.107_951_3],
+        6.283_213,
+    ),
+    broucke(
+        "Broucke A2",
+        [0.336_130_1, 0.769_989_4, -1.106_119_5],
+        [1.532_431_5, -0.628_735_1, -0.903_696_4],
+        7.702_408,
+    ),
+    broucke(
+        "Broucke A3",
+        [0.314_933_75, 0.812_382_07, -1.127_315_8],
+        [1.460_187, -0.562_829_2, -0.897_357_7],
+        7.910_268,
+    ),
+    broucke(
+        "Broucke R1",
+        [0.808_310_6, -0.495_414_86, -0.312_895_77],
+        [0.990_197_9, -2.717_143_2, 1.726_945_3],
+        5.226_525,
+    ),
+    // Choreographies of more than three bodies found numerically by Simó, from Chenciner, Gerver,
+    // Montgomery and Simó, "Simple choreographic motions of N bodies: a preliminary study" (2002).
+    // Recomputed with a period of 2π by solving for the Fourier coefficients of the curve.
+    Solution {
+        name: "Super-eight",
+        family: "Simó",
+        period: Some(TAU),
+        state: InitialState::Explicit(&[
+            ([0.0, 0.157_029_93], [1.871_935_2, 0.0]),
+            ([1.382_856_8, 0.0], [0.0, 0.584_872_6]),
+            ([0.0, -0.157_029_93], [-1.871_935_2, 0.0]),
+            ([-1.382_856_8, 0.0], [0.0, -0.584_872_6]),
+        ]),
+    },
+    Solution {
+        name: "Five-body eight",
+        family: "Simó",
+        period: Some(TAU),
+        state: InitialState::Explicit(&[
+            ([0.0, 0.0], [1.219_097, 1.250_686_3]),
+            ([1.360_274_4, 0.271_516_68], [0.545_478_34, -0.753_548_26]),
+            ([0.798_117_34, -0.484_379_05], [-1.155_026_8, 0.128_205_1]),
+            ([-0.798_117_34, 0.484_379_05], [-1.155_026_8, 0.128_205_1]),
+            ([-1.360_274_4, -0.271_516_68], [0.545_478_34, -0.753_548_26]),
+        ]),
+    },
+];
+
+pub fn find(name: &str) -> Option<&'static Solution> {
+    CATALOGUE.iter().find(|solution| solution.name == name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn accelerations(positions: &[Vec2]) -> Vec<Vec2> {
+        positions
+            .iter()
+            .map(|&p| {
+                positions
+                    .iter()
+                    .map(|&q| q - p)
+                    .filter(|d| *d != Vec2::ZERO)
+                    .fold(Vec2::ZERO, |a, d| a + d / d.length().powi(3))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn solutions_have_no_net_momentum() {
+        for solution in CATALOGUE {
+            let momentum: Vec2 = solution.bodies().iter().map(|(_, v)| v).sum();
+            assert!(momentum.length() < 1E-5, "{}", solution.name);
+        }
+    }
+
+    #[test]
+    fn short_solutions_return_after_a_period() {
+        for name in [
+            "Figure-eight",
+            "Broucke A2",
+            "Super-eight",
+            "Five-body eight",
+        ] {
+            let solution = find(name).unwrap();
+            let period = solution.period.unwrap();
+            let steps = 20_000;
+            let dt = period / steps as f32;
+
+            let (mut positions, mut velocities): (Vec<_>, Vec<_>) =
+                solution.bodies().into_iter().unzip();
+            let start = positions.clone();
+            for _ in 0..steps {
+                for (v, a) in velocities.iter_mut().zip(accelerations(&positions)) {
+                    *v += a * dt / 2.0;
+                }
+                for (p, v) in positions.iter_mut().zip(&velocities) {
+                    *p += *v * dt;
+                }
+                for (v, a) in velocities.iter_mut().zip(accelerations(&positions)) {
+                    *v += a * dt / 2.0;
+                }
+            }
+
+            for (p, q) in positions.iter().zip(&start) {
+                assert!(p.distance(*q) < 1E-2, "{name}: {p} != {q}");
+            }
+        }
+    }
+}
@@ -0,0 +1,102 @@
+mod catalogue;
+
+use std::f32::consts::PI;
+
+use bevy::{
+    ecs::system::{EntityCommands, Res},
+    prelude::{AssetServer, BuildChildren, Color, Name},
+};
+use bevy_egui::egui::{self, ScrollArea, TextEdit, Ui};
+use heron::Velocity;
+
+use self::catalogue::{find, Solution, CATALOGUE};
+use crate::{
+    nbody::PointMass,
+    simulation_scene::{SceneParams, Spawnable},
+    trails::Trail,
+    BodyBundle, SceneData, G,
+};
+
+/// Equal masses on a periodic solution picked from [`CATALOGUE`].
+#[derive(Clone, SceneParams)]
+pub struct Choreography {
+    #[param(default = "Figure-eight".to_string(), widget = pick_solution)]
+    solution: String,
+    /// Mass of each body.
+    #[param(default = 1E5, range = 1E3..=1E6, log)]
+    mass: f32,
+    /// Length in world units of the unit of the catalogue.
+    #[param(default = 450.0, range = 50.0..=2000.0, log, integer)]
+    size: f32,
+    #[param(default = 20.0, range = 5.0..=100.0, log, integer)]
+    radius: f32,
+}
+
+impl Choreography {
+    fn solution(&self) -> &'static Solution {
+        find(&self.solution).unwrap_or(&CATALOGUE[0])
+    }
+
+    /// Speed in world units of the unit of the catalogue, for which G times the mass of a body is 1.
+    fn speed_unit(&self) -> f32 {
+        (G * self.mass / self.size).sqrt()
+    }
+}
+
+fn pick_solution(ui: &mut Ui, selected: &mut String) {
+    let id = ui.id().with("solution_search");
+    let mut search = ui.data().get_temp_mut_or_default::<String>(id).clone();
+
+    ui.add(TextEdit::singleline(&mut search).hint_text("Search solutions"));
+    let filter = search.to_lowercase();
+
+    ScrollArea::vertical().max_height(160.0).show(ui, |ui| {
+        let mut family = None;
+        for solution in CATALOGUE.iter().filter(|s| {
+            s.name.to_lowercase().contains(&filter) || s.family.to_lowercase().contains(&filter)
+        }) {
+            if family != Some(solution.family) {
+                family = Some(solution.family);
+                ui.label(egui::RichText::new(solution.family).strong());
+            }
+            let label = ui.selectable_label(*selected == solution.name, solution.name);
+            if label.clicked() {
+                *selected = solution.name.to_string();
+            }
+        }
+    });
+
+    ui.data().insert_temp(id, search);
+}
+
+impl SceneData for Choreography {
+    fn instance(&self, mut scene_commands: EntityCommands, asset_server: Res<AssetServer>) {
+        let solution = self.solution();
+        let density = self.mass / (self.radius.powi(2) * PI);
+        let speed_unit = self.speed_unit();
+        let period = solution.period.map(|t| t * self.size / speed_unit);
+
+        scene_commands.with_children(|child| {
+            for (i, (position, velocity)) in solution.bodies().into_iter().enumerate() {
+                child
+                    .spawn_bundle(BodyBundle::new(
+                        (position * self.size).extend(0.0),
+                        Velocity::from_linear((velocity * speed_unit).extend(0.0)),
+                        density,
+                        self.mass,
+                        PointMass::HasGravity { mass: self.mass },
+                        Color::WHITE,
+                        &asset_server,
+                    ))
+                    .insert(Name::new(format!("{} {}", solution.name, i + 1)))
+                    .insert(Trail::new(period.unwrap_or(15.0).clamp(5.0, 60.0)));
+            }
+        });
+    }
+
+    fn spawnable(&self) -> Spawnable {
+        Spawnable::Massless {
+            density: 3E-7 * self.mass / (self.radius * self.radius * PI),
+        }
+    }
+}
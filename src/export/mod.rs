@@ -0,0 +1,122 @@
+mod svg;
+
+use std::path::PathBuf;
+
+use bevy::prelude::*;
+use bevy_egui::{egui::Window, EguiContext};
+use heron::CollisionShape;
+
+use self::svg::{to_svg, Snapshot, SnapshotBody, SnapshotTrail};
+use crate::{
+    nbody::PointMass,
+    reference_frame::Bodies,
+    trails::{Trail, TrailBuffers},
+};
+
+pub struct ExportPlugin;
+
+impl Plugin for ExportPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ExportSettings>()
+            .add_event::<ExportSvg>()
+            .add_system(export_window)
+            .add_system(export_svg);
+    }
+}
+
+pub struct ExportSettings {
+    pub scale_bar: bool,
+    /// Outcome of the last export, shown in the window.
+    status: Option<String>,
+}
+
+impl Default for ExportSettings {
+    fn default() -> Self {
+        Self {
+            scale_bar: true,
+            status: None,
+        }
+    }
+}
+
+/// Writes the bodies and trails to the next free `snapshot-N.svg` in the working directory.
+pub struct ExportSvg;
+
+fn export_window(
+    mut egui_ctx: ResMut<EguiContext>,
+    mut settings: ResMut<ExportSettings>,
+    mut export: EventWriter<ExportSvg>,
+) {
+    Window::new("Export").show(egui_ctx.ctx_mut(), |ui| {
+        ui.checkbox(&mut settings.scale_bar, "Scale bar");
+        if ui.button("Export SVG").clicked() {
+            export.send(ExportSvg);
+        }
+        if let Some(status) = &settings.status {
+            ui.label(status);
+        }
+    });
+}
+
+fn next_path() -> PathBuf {
+    (1..)
+        .map(|n| PathBuf::from(format!("snapshot-{n}.svg")))
+        .find(|path| !path.exists())
+        .unwrap()
+}
+
+fn export_svg(
+    mut events: EventReader<ExportSvg>,
+    mut settings: ResMut<ExportSettings>,
+    clear_color: Res<ClearColor>,
+    buffers: Res<TrailBuffers>,
+    bodies: Query<(&GlobalTransform, &CollisionShape, &Sprite), With<PointMass>>,
+    trails: Query<(Entity, &GlobalTransform, &Trail)>,
+    anchors: Bodies,
+) {
+    if events.iter().count() == 0 {
+        return;
+    }
+
+    let bodies = bodies
+        .iter()
+        .map(|(transform, shape, sprite)| SnapshotBody {
+            position: transform.translation().truncate(),
+            radius: match shape {
+                CollisionShape::Sphere { radius } => *radius,
+                _ => 1.0,
+            },
+            color: sprite.color,
+        })
+        .collect();
+
+    let trails = trails
+        .iter()
+        .filter_map(|(entity, transform, trail)| {
+            let buffer = buffers
+                .get(&entity)
+                .filter(|b| b.anchor() == trail.anchor)?;
+            let origin = trail.anchor.origin(&anchors)?;
+            let points: Vec<_> = buffer
+                .samples()
+                .map(|sample| (origin + sample.position).truncate())
+                .chain([transform.translation().truncate()])
+                .collect();
+            let color = buffer.samples().last()?.color;
+            Some(SnapshotTrail { points, color })
+        })
+        .collect();
+
+    let snapshot = Snapshot {
+        bodies,
+        trails,
+        background: Some(clear_color.0),
+        scale_bar: settings.scale_bar,
+    };
+
+    let path = next_path();
+    settings.status = Some(match std::fs::write(&path, to_svg(&snapshot)) {
+        Ok(()) => format!("Saved {}", path.display()),
+        Err(error) => format!("Export failed: {error}"),
+    });
+}
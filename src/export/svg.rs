@@ -0,0 +1,203 @@
+use std::fmt::Write;
+
+use bevy::{math::Vec2, render::color::Color};
+
+// Margin around the content, as a fraction of its largest dimension.
+const MARGIN: f32 = 0.05;
+
+#[derive(Clone, Copy)]
+pub struct SnapshotBody {
+    pub position: Vec2,
+    pub radius: f32,
+    pub color: Color,
+}
+
+pub struct SnapshotTrail {
+    pub points: Vec<Vec2>,
+    pub color: Color,
+}
+
+/// What an SVG export shows, in world coordinates.
+#[derive(Default)]
+pub struct Snapshot {
+    pub bodies: Vec<SnapshotBody>,
+    pub trails: Vec<SnapshotTrail>,
+    pub background: Option<Color>,
+    pub scale_bar: bool,
+}
+
+fn hex(color: Color) -> String {
+    let [r, g, b, _] = color
+        .as_rgba_f32()
+        .map(|c| (c.clamp(0.0, 1.0) * 255.0).round() as u8);
+    format!("#{r:02x}{g:02x}{b:02x}")
+}
+
+fn paint(attribute: &str, color: Color) -> String {
+    let alpha = color.a();
+    if alpha < 1.0 {
+        format!(
+            r#"{attribute}="{}" {attribute}-opacity="{alpha:.2}""#,
+            hex(color)
+        )
+    } else {
+        format!(r#"{attribute}="{}""#, hex(color))
+    }
+}
+
+// Mirrors a world y coordinate into SVG's downward axis, without printing negative zeros.
+fn flip(y: f32) -> f32 {
+    0.0 - y
+}
+
+/// Largest 1, 2 or 5 times a power of ten not above `length`.
+fn round_length(length: f32) -> f32 {
+    let magnitude = 10_f32.powf(length.log10().floor());
+    [5.0, 2.0, 1.0]
+        .into_iter()
+        .map(|factor| factor * magnitude)
+        .find(|candidate| *candidate <= length)
+        .unwrap_or(magnitude)
+}
+
+/// Renders a snapshot as an SVG document, with the y axis pointing up as on screen.
+pub fn to_svg(snapshot: &Snapshot) -> String {
+    let points = snapshot
+        .bodies
+        .iter()
+        .flat_map(|body| {
+            [
+                body.position - Vec2::splat(body.radius),
+                body.position + Vec2::splat(body.radius),
+            ]
+        })
+        .chain(
+            snapshot
+                .trails
+                .iter()
+                .flat_map(|t| t.points.iter().copied()),
+        );
+    let (min, max) = points.fold(
+        (Vec2::splat(f32::INFINITY), Vec2::splat(f32::NEG_INFINITY)),
+        |(min, max), point| (min.min(point), max.max(point)),
+    );
+    let (min, max) = if min.x <= max.x {
+        (min, max)
+    } else {
+        (Vec2::splat(-1.0), Vec2::splat(1.0))
+    };
+
+    let size = (max - min).max(Vec2::splat(1.0));
+    let margin = size.max_element() * MARGIN;
+    let (min, size) = (min - margin, size + 2.0 * margin);
+    // SVG's y axis points down, so world points are mirrored and the view box starts at -max.y.
+    let top = flip(min.y + size.y);
+
+    let mut svg = String::new();
+    let _ = writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="{:.2} {:.2} {:.2} {:.2}">"#,
+        min.x, top, size.x, size.y
+    );
+    if let Some(background) = snapshot.background {
+        let _ = writeln!(
+            svg,
+            r#"  <rect x="{:.2}" y="{:.2}" width="{:.2}" height="{:.2}" {}/>"#,
+            min.x,
+            top,
+            size.x,
+            size.y,
+            paint("fill", background)
+        );
+    }
+
+    let stroke_width = size.max_element() / 500.0;
+    for trail in snapshot.trails.iter().filter(|t| t.points.len() > 1) {
+        let points: Vec<_> = trail
+            .points
+            .iter()
+            .map(|p| format!("{:.2},{:.2}", p.x, flip(p.y)))
+            .collect();
+        let _ = writeln!(
+            svg,
+            r#"  <polyline points="{}" fill="none" {} stroke-width="{stroke_width:.2}"/>"#,
+            points.join(" "),
+            paint("stroke", trail.color)
+        );
+    }
+
+    for body in &snapshot.bodies {
+        let _ = writeln!(
+            svg,
+            r#"  <circle cx="{:.2}" cy="{:.2}" r="{:.2}" {}/>"#,
+            body.position.x,
+            flip(body.position.y),
+            body.radius,
+            paint("fill", body.color)
+        );
+    }
+
+    if snapshot.scale_bar {
+        let length = round_length(size.x / 5.0);
+        let (x, y) = (min.x + margin, top + size.y - margin);
+        let _ = writeln!(
+            svg,
+            r#"  <line x1="{x:.2}" y1="{y:.2}" x2="{:.2}" y2="{y:.2}" stroke="{}" stroke-width="{:.2}"/>"#,
+            x + length,
+            hex(Color::GRAY),
+            stroke_width * 2.0
+        );
+        let _ = writeln!(
+            svg,
+            r#"  <text x="{x:.2}" y="{:.2}" font-size="{:.2}" fill="{}">{length}</text>"#,
+            y - stroke_width * 4.0,
+            margin * 0.6,
+            hex(Color::GRAY)
+        );
+    }
+
+    svg.push_str("</svg>\n");
+    svg
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scale_bar_uses_round_lengths() {
+        assert_eq!(round_length(0.7), 0.5);
+        assert_eq!(round_length(37.0), 20.0);
+        assert_eq!(round_length(1000.0), 1000.0);
+    }
+
+    #[test]
+    fn matches_golden_file() {
+        let snapshot = Snapshot {
+            bodies: vec![
+                SnapshotBody {
+                    position: Vec2::new(0.0, 0.0),
+                    radius: 10.0,
+                    color: Color::YELLOW,
+                },
+                SnapshotBody {
+                    position: Vec2::new(100.0, 50.0),
+                    radius: 2.5,
+                    color: Color::rgba(0.0, 0.5, 1.0, 0.5),
+                },
+            ],
+            trails: vec![SnapshotTrail {
+                points: vec![
+                    Vec2::new(100.0, -50.0),
+                    Vec2::new(120.0, 0.0),
+                    Vec2::new(100.0, 50.0),
+                ],
+                color: Color::WHITE,
+            }],
+            background: Some(Color::BLACK),
+            scale_bar: true,
+        };
+
+        assert_eq!(to_svg(&snapshot), include_str!("testdata/snapshot.svg"));
+    }
+}
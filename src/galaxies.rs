@@ -0,0 +1,305 @@
+use std::f32::consts::{PI, TAU};
+
+use bevy::{
+    ecs::system::{EntityCommands, Res},
+    prelude::{AssetServer, BuildChildren, Color, Name, Vec2},
+};
+use heron::Velocity;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+use crate::{
+    nbody::PointMass,
+    simulation_scene::{seed_picker, SceneParams, Spawnable},
+    BodyBundle, SceneData, G,
+};
+
+/// Two disk galaxies on a parabolic encounter, throwing out tidal tails.
+///
+/// The simulation is planar, so each disk lies in the orbital plane and its inclination comes
+/// down to whether it spins with the orbit or against it. Prograde disks grow the longest tails.
+#[derive(Clone, SceneParams)]
+#[scene(name = "Galaxy collision")]
+pub struct GalaxyCollision {
+    #[param(default = 1E5, range = 1E4..=1E6, log, section = "Galaxies:")]
+    bulge_mass: f32,
+    /// Mass of the second galaxy relative to the first.
+    #[param(default = 1.0, range = 0.1..=1.0)]
+    mass_ratio: f32,
+    #[param(default = 400, range = 0..=2000, label = "Stars per disk")]
+    disk_stars: usize,
+    /// Scale length of the exponential disk of the first galaxy.
+    #[param(default = 80.0, range = 20.0..=300.0, log, integer)]
+    scale_length: f32,
+    /// Mass of the disk relative to the bulge, massless stars if zero.
+    #[param(default = 0.0, range = 0.0..=1.0, label = "Disk mass / bulge")]
+    disk_mass: f32,
+    #[param(default = 0, range = 0..=1000, label = "Halo particles per galaxy")]
+    halo_particles: usize,
+    /// Mass of the halo relative to the bulge, massless particles if zero.
+    #[param(default = 0.0, range = 0.0..=3.0, label = "Halo mass / bulge")]
+    halo_mass: f32,
+    #[param(
+        default = 300.0,
+        range = 50.0..=1000.0,
+        log,
+        integer,
+        section = "Encounter:"
+    )]
+    pericentre: f32,
+    /// Initial distance between the bulges.
+    #[param(
+        default = 1500.0,
+        range = self.pericentre..=5000.0,
+        log,
+        integer
+    )]
+    start_distance: f32,
+    #[param(default = false, label = "First disk retrograde")]
+    retrograde_first: bool,
+    #[param(default = false, label = "Second disk retrograde")]
+    retrograde_second: bool,
+    #[param(default = 20.0, hidden)]
+    bulge_density: f32,
+    #[param(default = 0.1, hidden)]
+    star_density: f32,
+    #[param(default = 0, widget = seed_picker)]
+    seed: u64,
+}
+
+struct Galaxy {
+    center: Vec2,
+    velocity: Vec2,
+    bulge_mass: f32,
+    scale_length: f32,
+    /// Radius of the bulge, inside which no star starts.
+    inner_radius: f32,
+    disk_mass: f32,
+    halo_mass: f32,
+    retrograde: bool,
+    color: Color,
+}
+
+/// A body orbiting a bulge, massless if its mass is zero.
+struct Star {
+    position: Vec2,
+    velocity: Vec2,
+    mass: f32,
+    color: Color,
+}
+
+/// Positions and velocities of two masses on a parabolic orbit, relative to their barycentre, when
+/// `distance` apart and closing in on a pericentre at `pericentre`.
+fn parabolic_encounter(masses: [f32; 2], pericentre: f32, distance: f32) -> [(Vec2, Vec2); 2] {
+    let total = masses[0] + masses[1];
+    let distance = distance.max(pericentre);
+    // r = 2q / (1 + cos f), negative true anomaly before the pericentre.
+    let anomaly = -(2.0 * pericentre / distance - 1.0).clamp(-1.0, 1.0).acos();
+    let radial = Vec2::from_angle(anomaly);
+    let speed = (G * total / (2.0 * pericentre)).sqrt();
+
+    let position = radial * distance;
+    let velocity = speed * (radial * anomaly.sin() + radial.perp() * (1.0 + anomaly.cos()));
+
+    [
+        (-position * masses[1] / total, -velocity * masses[1] / total),
+        (position * masses[0] / total, velocity * masses[0] / total),
+    ]
+}
+
+impl Galaxy {
+    /// Disk stars on circular orbits about the mass enclosed within their radius, and halo
+    /// particles moving at the same speed in random directions.
+    fn stars(&self, disk_stars: usize, halo_particles: usize, rng: &mut impl Rng) -> Vec<Star> {
+        // The disk starts at the bulge and is cut off after five scale lengths.
+        let width = 5.0 * self.scale_length;
+        let mut radii: Vec<(f32, bool)> = Vec::with_capacity(disk_stars + halo_particles);
+
+        while radii.len() < disk_stars {
+            // The radii of an exponential disk follow a gamma distribution of shape 2.
+            let (u, v): (f32, f32) = (rng.gen_range(f32::EPSILON..1.0), rng.gen());
+            let depth = -self.scale_length * (u * v.max(f32::EPSILON)).ln();
+            if depth < width {
+                radii.push((self.inner_radius + depth, true));
+            }
+        }
+        // An isothermal halo, whose surface density falls off as 1/R, out to twice the disk.
+        for _ in 0..halo_particles {
+            let radius = self.inner_radius + rng.gen_range(0.0..2.0 * width);
+            radii.push((radius, false));
+        }
+        radii.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        let star_mass = self.disk_mass / disk_stars.max(1) as f32;
+        let halo_particle_mass = self.halo_mass / halo_particles.max(1) as f32;
+        let mut enclosed = self.bulge_mass;
+
+        radii
+            .into_iter()
+            .map(|(radius, in_disk)| {
+                let mass = if in_disk {
+                    star_mass
+                } else {
+                    halo_particle_mass
+                };
+                let speed = (G * enclosed / radius).sqrt();
+                enclosed += mass;
+
+                let direction = Vec2::from_angle(rng.gen_range(0.0..TAU));
+                let (velocity, color) = if in_disk {
+                    let spin = if self.retrograde { -1.0 } else { 1.0 };
+                    (direction.perp() * speed * spin, self.color)
+                } else {
+                    let heading = Vec2::from_angle(rng.gen_range(0.0..TAU));
+                    (heading * speed, Color::GRAY)
+                };
+
+                Star {
+                    position: self.center + direction * radius,
+                    velocity: self.velocity + velocity,
+                    mass,
+                    color,
+                }
+            })
+            .collect()
+    }
+}
+
+impl GalaxyCollision {
+    fn galaxies(&self) -> [Galaxy; 2] {
+        let masses = [1.0, self.mass_ratio]
+            .map(|ratio| ratio * self.bulge_mass * (1.0 + self.disk_mass + self.halo_mass));
+        let orbits = parabolic_encounter(masses, self.pericentre, self.start_distance);
+
+        let galaxy = |i: usize, retrograde: bool, color: Color| {
+            let ratio = [1.0, self.mass_ratio][i];
+            let bulge_mass = self.bulge_mass * ratio;
+            Galaxy {
+                center: orbits[i].0,
+                velocity: orbits[i].1,
+                bulge_mass,
+                scale_length: self.scale_length * ratio.cbrt(),
+                inner_radius: 2.0 * (bulge_mass / (self.bulge_density * PI)).sqrt(),
+                disk_mass: self.disk_mass * bulge_mass,
+                halo_mass: self.halo_mass * bulge_mass,
+                retrograde,
+                color,
+            }
+        };
+
+        [
+            galaxy(0, self.retrograde_first, Color::rgb(0.6, 0.8, 1.0)),
+            galaxy(1, self.retrograde_second, Color::rgb(1.0, 0.8, 0.5)),
+        ]
+    }
+}
+
+impl SceneData for GalaxyCollision {
+    fn instance(&self, mut scene_commands: EntityCommands, asset_server: Res<AssetServer>) {
+        let mut rng = ChaCha8Rng::seed_from_u64(self.seed);
+
+        scene_commands.with_children(|child| {
+            for (i, galaxy) in self.galaxies().iter().enumerate() {
+                child
+                    .spawn_bundle(BodyBundle::new(
+                        galaxy.center.extend(0.0),
+                        Velocity::from_linear(galaxy.velocity.extend(0.0)),
+                        self.bulge_density,
+                        galaxy.bulge_mass,
+                        PointMass::HasGravity {
+                            mass: galaxy.bulge_mass,
+                        },
+                        Color::WHITE,
+                        &asset_server,
+                    ))
+                    .insert(Name::new(format!("Bulge {}", i + 1)));
+
+                for star in galaxy.stars(self.disk_stars, self.halo_particles, &mut rng) {
+                    let (density, physics_mass, point_mass) = if star.mass > 0.0 {
+                        (
+                            self.star_density,
+                            star.mass,
+                            PointMass::HasGravity { mass: star.mass },
+                        )
+                    } else {
+                        (
+                            self.star_density / 100.0,
+                            0.01,
+                            PointMass::AffectedByGravity,
+                        )
+                    };
+
+                    child.spawn_bundle(BodyBundle::new(
+                        star.position.extend(0.0),
+                        Velocity::from_linear(star.velocity.extend(0.0)),
+                        density,
+                        physics_mass,
+                        point_mass,
+                        star.color,
+                        &asset_server,
+                    ));
+                }
+            }
+        });
+    }
+
+    fn spawnable(&self) -> Spawnable {
+        Spawnable::Massive {
+            min_mass: 1.0,
+            max_mass: self.bulge_mass / 5E3,
+            density: self.star_density,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encounter_is_parabolic() {
+        let masses = [3E4, 1E4];
+        let [(p1, v1), (p2, v2)] = parabolic_encounter(masses, 200.0, 1000.0);
+
+        assert!((p1 * masses[0] + p2 * masses[1]).length() < 1E-2);
+        assert!((v1 * masses[0] + v2 * masses[1]).length() < 1E-2);
+
+        let (r, v) = (p2 - p1, v2 - v1);
+        assert!((r.length() - 1000.0).abs() < 1E-2);
+        let energy = v.length_squared() / 2.0 - G * (masses[0] + masses[1]) / r.length();
+        assert!(energy.abs() < 1E-2 * v.length_squared());
+        // Closing in, with the angular momentum of a parabola of pericentre q: h² = 2 G M q.
+        assert!(r.dot(v) < 0.0);
+        let h = r.perp_dot(v);
+        assert!((h * h / (2.0 * G * 4E4) - 200.0).abs() < 1.0);
+    }
+
+    #[test]
+    fn disk_stars_orbit_the_enclosed_mass() {
+        let galaxy = Galaxy {
+            center: Vec2::new(100.0, 0.0),
+            velocity: Vec2::new(0.0, 10.0),
+            bulge_mass: 1E5,
+            scale_length: 50.0,
+            inner_radius: 20.0,
+            disk_mass: 2E4,
+            halo_mass: 0.0,
+            retrograde: true,
+            color: Color::WHITE,
+        };
+
+        let stars = galaxy.stars(200, 0, &mut ChaCha8Rng::seed_from_u64(1));
+
+        assert_eq!(stars.len(), 200);
+        for star in &stars {
+            let offset = star.position - galaxy.center;
+            let velocity = star.velocity - galaxy.velocity;
+            assert!(offset.dot(velocity).abs() < 1E-2 * offset.length() * velocity.length());
+            // Retrograde disks turn clockwise.
+            assert!(offset.perp_dot(velocity) < 0.0);
+            let enclosed = velocity.length_squared() * offset.length() / G;
+            let (min, max) = (galaxy.bulge_mass, galaxy.bulge_mass + galaxy.disk_mass);
+            assert!((min * 0.999..=max * 1.001).contains(&enclosed));
+        }
+    }
+}
@@ -0,0 +1,264 @@
+mod systems;
+
+use bevy::prelude::*;
+use heron::{CollisionShape, PhysicMaterial, RigidBody, Velocity};
+
+use crate::{nbody::PointMass, spacecraft::Spacecraft, trails::Trail};
+
+pub struct HistoryPlugin;
+
+impl Plugin for HistoryPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<EditHistory>()
+            .add_event::<HistoryAction>()
+            .add_system(systems::clear_history)
+            .add_system(systems::history_keys)
+            .add_system(systems::edit_window)
+            .add_system(
+                systems::apply_history
+                    .after(systems::history_keys)
+                    .after(systems::edit_window),
+            )
+            .add_system_to_stage(CoreStage::PostUpdate, systems::record_spawns);
+    }
+}
+
+pub enum HistoryAction {
+    Undo,
+    Redo,
+    /// Deletes the selected body.
+    Delete,
+}
+
+/// The components of a body, enough to respawn it as it was.
+#[derive(Clone)]
+pub struct BodyRecord {
+    pub entity: Entity,
+    parent: Option<Entity>,
+    transform: Transform,
+    velocity: Velocity,
+    point_mass: PointMass,
+    material: PhysicMaterial,
+    shape: CollisionShape,
+    rigid_body: RigidBody,
+    sprite: Sprite,
+    texture: Handle<Image>,
+    trail: Option<Trail>,
+    name: Option<Name>,
+    spacecraft: Option<Spacecraft>,
+}
+
+#[derive(Clone)]
+pub enum Edit {
+    Spawn(BodyRecord),
+    Delete(BodyRecord),
+    Move {
+        entity: Entity,
+        from: Vec3,
+        to: Vec3,
+    },
+    Velocity {
+        entity: Entity,
+        from: Vec3,
+        to: Vec3,
+    },
+    Mass {
+        entity: Entity,
+        from: f32,
+        to: f32,
+    },
+}
+
+impl Edit {
+    fn entity(&self) -> Entity {
+        match self {
+            Edit::Spawn(record) | Edit::Delete(record) => record.entity,
+            Edit::Move { entity, .. }
+            | Edit::Velocity { entity, .. }
+            | Edit::Mass { entity, .. } => *entity,
+        }
+    }
+
+    /// The edit that reverts this one.
+    fn inverse(&self) -> Edit {
+        match self.clone() {
+            Edit::Spawn(record) => Edit::Delete(record),
+            Edit::Delete(record) => Edit::Spawn(record),
+            Edit::Move { entity, from, to } => Edit::Move {
+                entity,
+                from: to,
+                to: from,
+            },
+            Edit::Velocity { entity, from, to } => Edit::Velocity {
+                entity,
+                from: to,
+                to: from,
+            },
+            Edit::Mass { entity, from, to } => Edit::Mass {
+                entity,
+                from: to,
+                to: from,
+            },
+        }
+    }
+
+    /// Extends this edit with a later one of the same field, returning `false` if they differ.
+    fn merge(&mut self, edit: &Edit) -> bool {
+        match (self, edit) {
+            (
+                Edit::Move { entity, to, .. },
+                Edit::Move {
+                    entity: e, to: t, ..
+                },
+            )
+            | (
+                Edit::Velocity { entity, to, .. },
+                Edit::Velocity {
+                    entity: e, to: t, ..
+                },
+            ) if entity == e => {
+                *to = *t;
+                true
+            }
+            (
+                Edit::Mass { entity, to, .. },
+                Edit::Mass {
+                    entity: e, to: t, ..
+                },
+            ) if entity == e => {
+                *to = *t;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn remap(&mut self, old: Entity, new: Entity) {
+        let swap = |entity: &mut Entity| {
+            if *entity == old {
+                *entity = new;
+            }
+        };
+        match self {
+            Edit::Spawn(record) | Edit::Delete(record) => {
+                swap(&mut record.entity);
+                if let Some(trail) = &mut record.trail {
+                    trail.anchor.remap(old, new);
+                }
+            }
+            Edit::Move { entity, .. }
+            | Edit::Velocity { entity, .. }
+            | Edit::Mass { entity, .. } => swap(entity),
+        }
+    }
+}
+
+/// Undo and redo stacks of the edits made to the bodies of the loaded scene.
+#[derive(Default)]
+pub struct EditHistory {
+    undo: Vec<Edit>,
+    redo: Vec<Edit>,
+    /// Bodies spawned this frame, recorded once their components are in place.
+    spawned: Vec<Entity>,
+}
+
+impl EditHistory {
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    /// Records a body spawned by the user, so that it can be undone.
+    pub fn spawned(&mut self, entity: Entity) {
+        self.spawned.push(entity);
+    }
+
+    /// Records an edit, folding it into the last one if `merge` is set and it changes the same field.
+    pub fn record(&mut self, edit: Edit, merge: bool) {
+        self.redo.clear();
+        let merged = merge && self.undo.last_mut().is_some_and(|last| last.merge(&edit));
+        if !merged {
+            self.undo.push(edit);
+        }
+    }
+
+    /// Moves the last edit to the redo stack and returns the edit that reverts it.
+    fn undo(&mut self) -> Option<Edit> {
+        let edit = self.undo.pop()?;
+        let inverse = edit.inverse();
+        self.redo.push(edit);
+        Some(inverse)
+    }
+
+    /// Moves the last undone edit back to the undo stack and returns it.
+    fn redo(&mut self) -> Option<Edit> {
+        let edit = self.redo.pop()?;
+        self.undo.push(edit.clone());
+        Some(edit)
+    }
+
+    /// Points the edits of a respawned body to its new entity.
+    fn remap(&mut self, old: Entity, new: Entity) {
+        for edit in self.undo.iter_mut().chain(&mut self.redo) {
+            edit.remap(old, new);
+        }
+    }
+
+    fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+        self.spawned.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn moved(entity: Entity, from: f32, to: f32) -> Edit {
+        Edit::Move {
+            entity,
+            from: Vec3::X * from,
+            to: Vec3::X * to,
+        }
+    }
+
+    fn target(edit: &Edit) -> (Entity, Vec3) {
+        match edit {
+            Edit::Move { entity, to, .. } => (*entity, *to),
+            _ => panic!("not a move"),
+        }
+    }
+
+    #[test]
+    fn drags_merge_into_one_edit() {
+        let body = Entity::from_raw(1);
+        let mut history = EditHistory::default();
+        history.record(moved(body, 0.0, 1.0), false);
+        history.record(moved(body, 1.0, 2.0), true);
+        history.record(moved(body, 2.0, 3.0), false);
+
+        assert_eq!(target(&history.undo().unwrap()), (body, Vec3::X * 2.0));
+        assert_eq!(target(&history.undo().unwrap()), (body, Vec3::ZERO));
+        assert!(!history.can_undo());
+        assert_eq!(target(&history.redo().unwrap()), (body, Vec3::X * 2.0));
+    }
+
+    #[test]
+    fn respawned_bodies_are_remapped() {
+        let (old, new) = (Entity::from_raw(1), Entity::from_raw(2));
+        let mut history = EditHistory::default();
+        history.record(moved(old, 0.0, 1.0), false);
+        history.record(moved(old, 1.0, 2.0), false);
+        history.undo();
+
+        history.remap(old, new);
+
+        assert_eq!(target(&history.redo().unwrap()).0, new);
+        assert_eq!(target(&history.undo().unwrap()).0, new);
+        assert_eq!(target(&history.undo().unwrap()).0, new);
+    }
+}
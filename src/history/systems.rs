@@ -0,0 +1,337 @@
+use std::f32::consts::PI;
+
+use bevy::prelude::*;
+use bevy_egui::{
+    egui::{self, DragValue, Grid, Window},
+    EguiContext,
+};
+use heron::{Acceleration, CollisionShape, PhysicMaterial, RigidBody, Velocity};
+
+use super::{BodyRecord, Edit, EditHistory, HistoryAction};
+use crate::{
+    nbody::PointMass,
+    reference_frame::ReferenceFrame,
+    selection::{body_label, Selection},
+    spacecraft::Spacecraft,
+    trails::Trail,
+    LoadedScene,
+};
+
+type EditedBodies<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static mut Transform,
+        &'static mut Velocity,
+        &'static mut PointMass,
+        &'static mut CollisionShape,
+        &'static PhysicMaterial,
+        &'static mut Sprite,
+    ),
+>;
+
+/// Gives a body a new mass, resizing its collider and sprite so that its density is unchanged.
+fn set_mass(
+    mass: f32,
+    point_mass: &mut PointMass,
+    shape: &mut CollisionShape,
+    material: &PhysicMaterial,
+    sprite: &mut Sprite,
+) {
+    *point_mass = PointMass::HasGravity { mass };
+    // Bodies without mass keep the small physical mass of massless bodies.
+    let radius = (mass.max(0.01) / (material.density * PI)).sqrt();
+    *shape = CollisionShape::Sphere { radius };
+    sprite.custom_size = Some(Vec2::splat(radius * 2.0));
+}
+
+type RecordedBodies<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static Transform,
+        &'static Velocity,
+        &'static PointMass,
+        &'static PhysicMaterial,
+        &'static CollisionShape,
+        &'static RigidBody,
+        &'static Sprite,
+        &'static Handle<Image>,
+        Option<&'static Trail>,
+        Option<&'static Name>,
+        Option<&'static Spacecraft>,
+        Option<&'static Parent>,
+    ),
+>;
+
+fn body_record(entity: Entity, bodies: &RecordedBodies) -> Option<BodyRecord> {
+    let (
+        transform,
+        velocity,
+        point_mass,
+        material,
+        shape,
+        rigid_body,
+        sprite,
+        texture,
+        trail,
+        name,
+        spacecraft,
+        parent,
+    ) = bodies.get(entity).ok()?;
+
+    Some(BodyRecord {
+        entity,
+        parent: parent.map(|parent| parent.get()),
+        transform: *transform,
+        velocity: *velocity,
+        point_mass: *point_mass,
+        material: *material,
+        shape: shape.clone(),
+        rigid_body: *rigid_body,
+        sprite: sprite.clone(),
+        texture: texture.clone(),
+        trail: trail.cloned(),
+        name: name.cloned(),
+        spacecraft: spacecraft.copied(),
+    })
+}
+
+fn respawn(commands: &mut Commands, record: BodyRecord) -> Entity {
+    let mut entity = commands.spawn_bundle(SpriteBundle {
+        transform: record.transform,
+        sprite: record.sprite,
+        texture: record.texture,
+        ..default()
+    });
+    entity.insert_bundle((
+        record.shape,
+        record.material,
+        record.rigid_body,
+        record.velocity,
+        Acceleration::default(),
+        record.point_mass,
+    ));
+    if let Some(trail) = record.trail {
+        entity.insert(trail);
+    }
+    if let Some(name) = record.name {
+        entity.insert(name);
+    }
+    if let Some(spacecraft) = record.spacecraft {
+        entity.insert(spacecraft);
+    }
+
+    let id = entity.id();
+    if let Some(parent) = record.parent {
+        commands.entity(parent).add_child(id);
+    }
+    id
+}
+
+// The entities of the previous scene are gone, so are the edits made to them.
+pub fn clear_history(scene: Res<LoadedScene>, mut history: ResMut<EditHistory>) {
+    if scene.is_changed() {
+        history.clear();
+    }
+}
+
+pub fn record_spawns(mut history: ResMut<EditHistory>, bodies: RecordedBodies) {
+    for entity in std::mem::take(&mut history.spawned) {
+        if let Some(record) = body_record(entity, &bodies) {
+            history.record(Edit::Spawn(record), false);
+        }
+    }
+}
+
+pub fn history_keys(
+    keys: Res<Input<KeyCode>>,
+    mut egui_ctx: ResMut<EguiContext>,
+    mut actions: EventWriter<HistoryAction>,
+) {
+    if egui_ctx.ctx_mut().wants_keyboard_input() {
+        return;
+    }
+
+    let ctrl = keys.any_pressed([KeyCode::LControl, KeyCode::RControl]);
+    let shift = keys.any_pressed([KeyCode::LShift, KeyCode::RShift]);
+    if ctrl && keys.just_pressed(KeyCode::Z) {
+        actions.send(if shift {
+            HistoryAction::Redo
+        } else {
+            HistoryAction::Undo
+        });
+    }
+    if keys.just_pressed(KeyCode::Delete) {
+        actions.send(HistoryAction::Delete);
+    }
+}
+
+pub fn apply_history(
+    mut commands: Commands,
+    mut actions: EventReader<HistoryAction>,
+    mut history: ResMut<EditHistory>,
+    mut selection: ResMut<Selection>,
+    mut frame: ResMut<ReferenceFrame>,
+    mut bodies: ParamSet<(RecordedBodies, EditedBodies, Query<&mut Trail>)>,
+) {
+    for action in actions.iter() {
+        let edit = match action {
+            HistoryAction::Undo => history.undo(),
+            HistoryAction::Redo => history.redo(),
+            HistoryAction::Delete => {
+                let record = selection.body().and_then(|e| body_record(e, &bodies.p0()));
+                if let Some(record) = &record {
+                    history.record(Edit::Delete(record.clone()), false);
+                }
+                record.map(Edit::Delete)
+            }
+        };
+
+        match edit {
+            Some(Edit::Spawn(record)) => {
+                let old = record.entity;
+                let new = respawn(&mut commands, record);
+                history.remap(old, new);
+                selection.remap(old, new);
+                frame.remap(old, new);
+                for mut trail in bodies.p2().iter_mut() {
+                    trail.anchor.remap(old, new);
+                }
+            }
+            Some(Edit::Delete(record)) if bodies.p0().contains(record.entity) => {
+                commands.entity(record.entity).despawn_recursive();
+            }
+            Some(Edit::Delete(_)) => {}
+            Some(edit) => {
+                if let Ok((
+                    mut transform,
+                    mut velocity,
+                    mut point_mass,
+                    mut shape,
+                    material,
+                    mut sprite,
+                )) = bodies.p1().get_mut(edit.entity())
+                {
+                    match edit {
+                        Edit::Move { to, .. } => transform.translation = to,
+                        Edit::Velocity { to, .. } => velocity.linear = to,
+                        Edit::Mass { to, .. } => {
+                            set_mass(to, &mut point_mass, &mut shape, material, &mut sprite)
+                        }
+                        Edit::Spawn(_) | Edit::Delete(_) => unreachable!(),
+                    }
+                }
+            }
+            None => {}
+        }
+    }
+}
+
+pub fn edit_window(
+    mut egui_ctx: ResMut<EguiContext>,
+    mut history: ResMut<EditHistory>,
+    mut actions: EventWriter<HistoryAction>,
+    selection: Res<Selection>,
+    mut bodies: EditedBodies,
+    names: Query<Option<&Name>>,
+) {
+    Window::new("Edit").show(egui_ctx.ctx_mut(), |ui| {
+        ui.with_layout(egui::Layout::left_to_right(egui::Align::Min), |ui| {
+            if ui
+                .add_enabled(history.can_undo(), egui::Button::new("Undo"))
+                .on_hover_text("Ctrl+Z")
+                .clicked()
+            {
+                actions.send(HistoryAction::Undo);
+            }
+            if ui
+                .add_enabled(history.can_redo(), egui::Button::new("Redo"))
+                .on_hover_text("Ctrl+Shift+Z")
+                .clicked()
+            {
+                actions.send(HistoryAction::Redo);
+            }
+        });
+        ui.separator();
+
+        let Some((
+            entity,
+            (mut transform, mut velocity, mut point_mass, mut shape, material, mut sprite),
+        )) = selection
+            .body()
+            .and_then(|e| Some((e, bodies.get_mut(e).ok()?)))
+        else {
+            ui.label("Click a body to edit it.");
+            return;
+        };
+        let name = names.get(entity).ok().flatten();
+        ui.label(format!("Body: {}", body_label(entity, name)));
+
+        // Dragging a value records a single edit from where the drag started.
+        let edit_vec = |ui: &mut egui::Ui, value: Vec3, speed: f32| {
+            let mut edited = value;
+            let x = ui.add(DragValue::new(&mut edited.x).speed(speed));
+            let y = ui.add(DragValue::new(&mut edited.y).speed(speed));
+            ui.end_row();
+            let merge = (x.dragged() && !x.drag_started()) || (y.dragged() && !y.drag_started());
+            (edited != value).then_some((edited, merge))
+        };
+
+        Grid::new("edit_body").num_columns(3).show(ui, |ui| {
+            ui.label("Position");
+            let position = transform.translation;
+            if let Some((to, merge)) = edit_vec(ui, position, 1.0) {
+                transform.translation = to;
+                history.record(
+                    Edit::Move {
+                        entity,
+                        from: position,
+                        to,
+                    },
+                    merge,
+                );
+            }
+
+            ui.label("Velocity");
+            let linear = velocity.linear;
+            if let Some((to, merge)) = edit_vec(ui, linear, 0.5) {
+                velocity.linear = to;
+                history.record(
+                    Edit::Velocity {
+                        entity,
+                        from: linear,
+                        to,
+                    },
+                    merge,
+                );
+            }
+
+            if let PointMass::HasGravity { mass } = *point_mass {
+                ui.label("Mass");
+                let mut edited = mass;
+                let response = ui.add(
+                    DragValue::new(&mut edited)
+                        .speed(mass.max(1.0) * 0.01)
+                        .clamp_range(0.0..=f32::MAX),
+                );
+                ui.end_row();
+                if edited != mass {
+                    set_mass(edited, &mut point_mass, &mut shape, material, &mut sprite);
+                    history.record(
+                        Edit::Mass {
+                            entity,
+                            from: mass,
+                            to: edited,
+                        },
+                        response.dragged() && !response.drag_started(),
+                    );
+                }
+            }
+        });
+
+        if ui.button("Delete").on_hover_text("Del").clicked() {
+            actions.send(HistoryAction::Delete);
+        }
+    });
+}
@@ -1,16 +1,40 @@
+mod analysis;
+mod choreography;
+mod export;
+mod galaxies;
+mod history;
 mod nbody;
+mod orbit;
+mod reference_frame;
+mod selection;
+mod shapes;
 mod simulation_scene;
 mod simulation_scenes;
+mod snapshot;
+mod solar_system;
+mod spacecraft;
 mod trails;
+mod units;
 
 use std::f32::consts::PI;
 use std::time::Duration;
 
+use analysis::AnalysisPlugin;
 use bevy_egui::egui;
+use choreography::Choreography;
+use export::ExportPlugin;
+use galaxies::GalaxyCollision;
+use history::{EditHistory, HistoryPlugin};
 use nbody::{ParticularPlugin, PointMass};
+use orbit::OrbitPlugin;
+use reference_frame::{FrameTransform, ReferenceFramePlugin};
+use selection::{PointerCapture, Selection, SelectionPlugin};
 use simulation_scene::*;
-use simulation_scenes::{DoubleOval, Figure8, Orbits, TernaryOrbit};
-use trails::{Trail, TrailsPlugin};
+use simulation_scenes::Orbits;
+use snapshot::SnapshotPlugin;
+use solar_system::SolarSystem;
+use spacecraft::{Spacecraft, SpacecraftPlugin};
+use trails::{Gradient, Trail, TrailAnchor, TrailColor, TrailsPlugin};
 
 use bevy::diagnostic::{Diagnostics, FrameTimeDiagnosticsPlugin, LogDiagnosticsPlugin};
 use bevy::input::mouse::MouseButtonInput;
@@ -53,17 +77,51 @@ fn main() {
         .add_plugin(TrailsPlugin)
         .add_plugin(ParticularPlugin)
         .add_plugin(SimulationScenePlugin)
+        .add_plugin(SelectionPlugin)
+        .add_plugin(OrbitPlugin)
+        .add_plugin(ReferenceFramePlugin)
+        .add_plugin(AnalysisPlugin)
+        .add_plugin(SpacecraftPlugin)
+        .add_plugin(ExportPlugin)
+        .add_plugin(SnapshotPlugin)
+        .add_plugin(HistoryPlugin)
         .insert_resource(ClearColor(Color::BLACK))
         .insert_resource(PhysicsSteps::from_steps_per_seconds(60.0))
         .insert_resource(
             SceneCollection::new()
-                .with_scene::<Empty>()
-                .with_scene::<Orbits>()
-                .with_scene::<Figure8>()
-                .with_scene::<DoubleOval>()
-                .with_scene::<TernaryOrbit>(),
+                .with_scene::<Empty>(
+                    SceneInfo::new("empty", "Empty")
+                        .with_description("No bodies, place your own.")
+                        .with_category("Sandbox"),
+                )
+                .with_scene::<Orbits>(
+                    SceneInfo::new("orbits", "Orbits")
+                        .with_description("Many small bodies on circular orbits around a star.")
+                        .with_category("Systems"),
+                )
+                .with_scene::<SolarSystem>(
+                    SceneInfo::new("solar_system", "Solar system")
+                        .with_description(
+                            "The Sun, the planets, the Moon and the asteroid belt at the J2000 epoch.",
+                        )
+                        .with_category("Systems"),
+                )
+                .with_scene::<GalaxyCollision>(
+                    SceneInfo::new("galaxy_collision", "Galaxy collision")
+                        .with_description(
+                            "Two disk galaxies pass each other on a parabolic orbit and throw out tidal tails.",
+                        )
+                        .with_category("Systems"),
+                )
+                .with_scene::<Choreography>(
+                    SceneInfo::new("choreography", "Choreography")
+                        .with_description(
+                            "Equal masses on a periodic orbit from a catalogue of known solutions.",
+                        )
+                        .with_category("Choreographies"),
+                ),
         )
-        .insert_resource(LoadedScene::new(Orbits::default()))
+        .insert_resource(LoadedScene::new("orbits", Orbits::default()))
         .init_resource::<BodyInfo>()
         .add_state(SimulationState::Running)
         .add_startup_system(spawn_camera)
@@ -161,6 +219,10 @@ struct BodyInfo {
     mass: f32,
     with_mass: bool,
     with_trail: bool,
+    trail_color: TrailColor,
+    trail_anchor: TrailAnchor,
+    trail_width: f32,
+    ship: bool,
 }
 
 impl Default for BodyInfo {
@@ -170,6 +232,10 @@ impl Default for BodyInfo {
             mass: 20.0,
             with_mass: true,
             with_trail: false,
+            trail_color: TrailColor::Body,
+            trail_anchor: TrailAnchor::View,
+            trail_width: 2.0,
+            ship: false,
         }
     }
 }
@@ -178,20 +244,71 @@ fn body_info_window(
     mut egui_ctx: ResMut<EguiContext>,
     mut body_info: ResMut<BodyInfo>,
     scene: Res<LoadedScene>,
+    selection: Res<Selection>,
 ) {
     Window::new("Body spawner").show(egui_ctx.ctx_mut(), |ui| {
         ui.with_layout(egui::Layout::left_to_right(egui::Align::Min), |ui| {
             if let Some((min_mass, max_mass)) = scene.spawnable().mass_range() {
                 ui.add_enabled(
-                    body_info.with_mass,
+                    body_info.with_mass && !body_info.ship,
                     Slider::new(&mut body_info.mass, min_mass..=max_mass),
                 );
 
-                ui.toggle_value(&mut body_info.with_mass, "Mass");
+                ui.add_enabled_ui(!body_info.ship, |ui| {
+                    ui.toggle_value(&mut body_info.with_mass, "Mass");
+                });
             }
         });
 
+        ui.checkbox(&mut body_info.ship, "Ship");
+
         ui.checkbox(&mut body_info.with_trail, "Draw trail");
+        ui.add_enabled_ui(body_info.with_trail, |ui| {
+            ui.horizontal(|ui| {
+                let options = [
+                    ("Body colour", TrailColor::Body),
+                    ("Speed", TrailColor::Speed(Gradient::default())),
+                    (
+                        "Acceleration",
+                        TrailColor::Acceleration(Gradient {
+                            max: 500.0,
+                            ..default()
+                        }),
+                    ),
+                ];
+                for (label, color) in options {
+                    let selected = std::mem::discriminant(&body_info.trail_color)
+                        == std::mem::discriminant(&color);
+                    if ui.selectable_label(selected, label).clicked() {
+                        body_info.trail_color = color;
+                    }
+                }
+            });
+
+            ui.horizontal(|ui| {
+                ui.label("Relative to");
+                let mut options = vec![
+                    ("View", TrailAnchor::View),
+                    ("World", TrailAnchor::World),
+                    ("Barycentre", TrailAnchor::Barycentre),
+                ];
+                options.extend(
+                    selection
+                        .body()
+                        .map(|e| ("Selected body", TrailAnchor::Body(e))),
+                );
+                if let TrailAnchor::Body(e) = body_info.trail_anchor {
+                    if selection.body() != Some(e) {
+                        options.push(("Previous body", body_info.trail_anchor));
+                    }
+                }
+                for (label, anchor) in options {
+                    ui.selectable_value(&mut body_info.trail_anchor, anchor, label);
+                }
+            });
+
+            ui.add(Slider::new(&mut body_info.trail_width, 0.0..=10.0).text("Trail width"));
+        });
     });
 
     if egui_ctx.ctx_mut().wants_pointer_input() {
@@ -199,6 +316,7 @@ fn body_info_window(
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn place_body(
     mut commands: Commands,
     mut click_event: EventReader<MouseButtonInput>,
@@ -206,49 +324,62 @@ fn place_body(
     mut body_info: ResMut<BodyInfo>,
     mouse_pos: Res<MousePosWorld>,
     scene: Res<LoadedScene>,
+    mut selection: ResMut<Selection>,
+    capture: Res<PointerCapture>,
+    mut history: ResMut<EditHistory>,
+    frame: Res<FrameTransform>,
     asset_server: Res<AssetServer>,
 ) {
     let mouse_pos = mouse_pos.truncate().extend(0.0);
 
+    if let Some(place_pos) = &mut body_info.position {
+        *place_pos = frame.reanchor(*place_pos);
+    }
+
     for event in click_event.iter() {
         if event.button == MouseButton::Left {
             match event.state {
-                ButtonState::Pressed => body_info.position = Some(mouse_pos),
+                ButtonState::Pressed if selection.hovered().is_none() && !capture.0 => {
+                    body_info.position = Some(mouse_pos)
+                }
+                ButtonState::Pressed => {}
                 ButtonState::Released => {
                     if let Some(place_pos) = body_info.position.take() {
-                        let spawnable = scene.spawnable();
-                        let (density, physics_mass, point_mass) = if spawnable.is_massive() {
-                            if body_info.with_mass {
-                                (
-                                    spawnable.density(),
-                                    body_info.mass,
-                                    PointMass::HasGravity {
-                                        mass: body_info.mass,
-                                    },
-                                )
-                            } else {
-                                (0.001, 0.01, PointMass::AffectedByGravity)
-                            }
-                        } else {
-                            (spawnable.density(), 0.01, PointMass::AffectedByGravity)
-                        };
+                        let mass =
+                            (body_info.with_mass && !body_info.ship).then_some(body_info.mass);
+                        let (density, physics_mass, point_mass) = scene.spawnable().body(mass);
                         let mut entity = commands.entity(scene.entity());
-                        println!("{}", density);
 
                         entity.with_children(|child| {
                             let mut entity = child.spawn_bundle(BodyBundle::new(
                                 place_pos,
-                                Velocity::from_linear(place_pos - mouse_pos),
+                                Velocity::from_linear(
+                                    place_pos - mouse_pos + frame.velocity_at(place_pos),
+                                ),
                                 density,
                                 physics_mass,
                                 point_mass,
-                                Color::WHITE,
+                                if body_info.ship {
+                                    Color::ORANGE
+                                } else {
+                                    Color::WHITE
+                                },
                                 &asset_server,
                             ));
 
                             if body_info.with_trail {
-                                entity.insert(Trail::new(20.0, 1));
+                                entity.insert(
+                                    Trail::new(20.0)
+                                        .with_color(body_info.trail_color)
+                                        .with_anchor(body_info.trail_anchor)
+                                        .with_width(body_info.trail_width),
+                                );
+                            }
+                            if body_info.ship {
+                                entity.insert(Spacecraft::default());
+                                selection.select(entity.id());
                             }
+                            history.spawned(entity.id());
                         });
                     }
                 }
@@ -1,9 +1,21 @@
-use crate::G;
+use crate::{
+    orbit::{kepler, OrbitalElements},
+    spacecraft::Spacecraft,
+    G,
+};
 
 use bevy::math::Vec3;
 use bevy::prelude::*;
-use heron::{should_run, Acceleration};
+use bevy_egui::{
+    egui::{Slider, Window},
+    EguiContext,
+};
+use heron::{
+    rapier_plugin::rapier2d::prelude::IntegrationParameters, should_run, Acceleration,
+    CollisionShape, RigidBody, Velocity,
+};
 use particular::prelude::*;
+use serde::{Deserialize, Serialize};
 
 #[particle(3)]
 pub struct Body {
@@ -20,19 +32,43 @@ impl Body {
             entity,
         }
     }
+
+    pub fn position(&self) -> Vec3 {
+        self.position
+    }
+
+    pub fn mu(&self) -> f32 {
+        self.mu
+    }
+
+    pub fn entity(&self) -> Entity {
+        self.entity
+    }
 }
 
-#[derive(Component)]
+#[derive(Clone, Copy, Debug, PartialEq, Component, Serialize, Deserialize)]
 pub enum PointMass {
     HasGravity { mass: f32 },
     AffectedByGravity,
 }
 
+impl PointMass {
+    pub fn mass(&self) -> f32 {
+        match self {
+            PointMass::HasGravity { mass } => *mass,
+            PointMass::AffectedByGravity => 0.0,
+        }
+    }
+}
+
 pub struct ParticularPlugin;
 
 impl Plugin for ParticularPlugin {
     fn build(&self, app: &mut bevy::prelude::App) {
         app.insert_resource(ParticleSet::<Body>::new())
+            .init_resource::<SimulationTime>()
+            .init_resource::<KeplerMode>()
+            .add_system(kepler_window)
             .add_system_set_to_stage(
                 CoreStage::PreUpdate,
                 SystemSet::new()
@@ -43,14 +79,17 @@ impl Plugin for ParticularPlugin {
                 CoreStage::Update,
                 SystemSet::new()
                     .with_run_criteria(should_run)
-                    .with_system(accelerate_particles),
+                    .with_system(accelerate_particles)
+                    .with_system(advance_time)
+                    .with_system(enter_kepler_orbits.after(accelerate_particles))
+                    .with_system(propagate_kepler_orbits.after(accelerate_particles)),
             );
     }
 }
 
 fn sync_particle_set(
     mut particle_set: ResMut<ParticleSet<Body>>,
-    query: Query<(Entity, &GlobalTransform, &PointMass)>,
+    query: Query<(Entity, &GlobalTransform, &PointMass), Without<KeplerOrbit>>,
 ) {
     *particle_set = ParticleSet::new();
     query.for_each(|(entity, tranform, point_mass)| {
@@ -66,7 +105,7 @@ fn sync_particle_set(
     })
 }
 
-fn accelerate_particles(
+pub fn accelerate_particles(
     mut particle_set: ResMut<ParticleSet<Body>>,
     mut query: Query<&mut Acceleration, With<PointMass>>,
 ) {
@@ -76,3 +115,295 @@ fn accelerate_particles(
         }
     }
 }
+
+/// Time elapsed in the simulation, counted in physics steps so that it stops while paused.
+#[derive(Default)]
+pub struct SimulationTime(pub f64);
+
+pub fn advance_time(mut time: ResMut<SimulationTime>, integration: Res<IntegrationParameters>) {
+    time.0 += integration.dt as f64;
+}
+
+/// A copy of the massive bodies of a [`ParticleSet`] and of some massless probes, integrated ahead of
+/// the live simulation.
+pub struct Forecast {
+    particle_set: ParticleSet<Body>,
+    velocities: Vec<Vec3>,
+    massive: usize,
+}
+
+impl Forecast {
+    /// Integrates probes against `attractors`, usually the massive bodies of the live [`ParticleSet`].
+    pub fn new<'a>(
+        attractors: impl IntoIterator<Item = &'a Body>,
+        velocity: impl Fn(Entity) -> Vec3,
+    ) -> Self {
+        let mut forecast = ParticleSet::new();
+        let mut velocities = Vec::new();
+        for body in attractors {
+            forecast.add_massive(Body::new(body.position, body.mu, body.entity));
+            velocities.push(velocity(body.entity));
+        }
+
+        Self {
+            particle_set: forecast,
+            massive: velocities.len(),
+            velocities,
+        }
+    }
+
+    /// Adds a massless probe and returns its index.
+    pub fn add_probe(&mut self, entity: Entity, position: Vec3, velocity: Vec3) -> usize {
+        self.particle_set
+            .add_massless(Body::new(position, 0.0, entity));
+        self.velocities.push(velocity);
+        self.velocities.len() - self.massive - 1
+    }
+
+    pub fn probe(&self, index: usize) -> (Vec3, Vec3) {
+        let body = self.particle_set.massless().nth(index).unwrap();
+        (body.position, self.velocities[self.massive + index])
+    }
+
+    pub fn set_probe_velocity(&mut self, index: usize, velocity: Vec3) {
+        self.velocities[self.massive + index] = velocity;
+    }
+
+    /// Positions, velocities and gravitational parameters of the massive bodies.
+    pub fn massive(&self) -> impl Iterator<Item = (Vec3, Vec3, f32)> + '_ {
+        self.particle_set
+            .massive()
+            .zip(&self.velocities)
+            .map(|(body, velocity)| (body.position, *velocity, body.mu))
+    }
+
+    /// Advances every body by `dt` with the same semi-implicit Euler step as the physics engine.
+    pub fn step(&mut self, dt: f32) {
+        for ((acceleration, body), velocity) in self.particle_set.result().zip(&mut self.velocities)
+        {
+            *velocity += acceleration * dt;
+            body.position += *velocity * dt;
+        }
+    }
+}
+
+/// Opt-in analytic propagation of massless bodies dominated by a single attractor.
+pub struct KeplerMode {
+    pub enabled: bool,
+    /// Largest ratio between the pull of the other attractors and the dominant one.
+    pub tolerance: f32,
+    /// Number of steps between two checks of the dominance of the primary.
+    pub check_interval: u32,
+}
+
+impl Default for KeplerMode {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            tolerance: 1E-3,
+            check_interval: 10,
+        }
+    }
+}
+
+/// State of a massless body relative to the primary it is analytically orbiting.
+#[derive(Component)]
+pub struct KeplerOrbit {
+    primary: Entity,
+    position: Vec3,
+    velocity: Vec3,
+    steps: u32,
+}
+
+// Ratio of the pull of all attractors but `primary` to the pull of `primary` at `position`.
+fn perturbation(position: Vec3, primary: Entity, particle_set: &ParticleSet<Body>) -> f32 {
+    let (dominant, others) =
+        particle_set
+            .massive()
+            .fold((Vec3::ZERO, Vec3::ZERO), |(dominant, others), body| {
+                let direction = body.position - position;
+                let acceleration = direction * body.mu / direction.length().powi(3);
+                if body.entity == primary {
+                    (dominant + acceleration, others)
+                } else {
+                    (dominant, others + acceleration)
+                }
+            });
+
+    // A body sitting on a massive one gets no meaningful ratio, which counts as perturbed.
+    let ratio = others.length() / dominant.length();
+    if ratio.is_finite() {
+        ratio
+    } else {
+        f32::INFINITY
+    }
+}
+
+// Hands a body back to the physics engine, without the acceleration it had before its Kepler orbit.
+fn leave_kepler_orbit(commands: &mut Commands, entity: Entity) {
+    commands
+        .entity(entity)
+        .remove::<KeplerOrbit>()
+        .insert(RigidBody::Dynamic)
+        .insert(Acceleration::default());
+}
+
+fn enter_kepler_orbits(
+    mut commands: Commands,
+    mut steps: Local<u32>,
+    kepler: Res<KeplerMode>,
+    particle_set: Res<ParticleSet<Body>>,
+    velocities: Query<&Velocity>,
+    shapes: Query<&CollisionShape>,
+    ships: Query<(), With<Spacecraft>>,
+) {
+    *steps += 1;
+    if !kepler.enabled || *steps < kepler.check_interval {
+        return;
+    }
+    *steps = 0;
+
+    // Ships can thrust at any time, so they are always left to the physics engine.
+    for particle in particle_set
+        .massless()
+        .filter(|particle| !ships.contains(particle.entity))
+    {
+        let primary = particle_set.massive().max_by(|a, b| {
+            let pull = |body: &&Body| body.mu / body.position.distance_squared(particle.position);
+            pull(a).total_cmp(&pull(b))
+        });
+        let primary = match primary {
+            Some(primary) => primary,
+            None => return,
+        };
+
+        if perturbation(particle.position, primary.entity, &particle_set) > kepler.tolerance {
+            continue;
+        }
+
+        let (velocity, primary_velocity) = match (
+            velocities.get(particle.entity),
+            velocities.get(primary.entity),
+        ) {
+            (Ok(velocity), Ok(primary_velocity)) => (velocity, primary_velocity),
+            _ => continue,
+        };
+        let position = particle.position - primary.position;
+        let velocity = velocity.linear - primary_velocity.linear;
+
+        // Leave orbits grazing the primary to the physics engine so collisions still happen.
+        let elements = OrbitalElements::from_state(position, velocity, primary.mu);
+        let primary_radius = match shapes.get(primary.entity) {
+            Ok(CollisionShape::Sphere { radius }) => *radius,
+            _ => 0.0,
+        };
+        if elements.periapsis <= 2.0 * primary_radius {
+            continue;
+        }
+
+        commands
+            .entity(particle.entity)
+            .insert(RigidBody::KinematicPositionBased)
+            .insert(KeplerOrbit {
+                primary: primary.entity,
+                position,
+                velocity,
+                steps: 0,
+            });
+    }
+}
+
+fn propagate_kepler_orbits(
+    mut commands: Commands,
+    kepler: Res<KeplerMode>,
+    integration: Res<IntegrationParameters>,
+    particle_set: Res<ParticleSet<Body>>,
+    mut orbits: Query<(Entity, &mut KeplerOrbit, &mut Transform, &mut Velocity)>,
+    primaries: Query<
+        (&GlobalTransform, &Velocity, &Acceleration, &PointMass),
+        Without<KeplerOrbit>,
+    >,
+) {
+    let dt = integration.dt;
+
+    for (entity, mut orbit, mut transform, mut velocity) in orbits.iter_mut() {
+        let primary = primaries.get(orbit.primary).ok().filter(|_| kepler.enabled);
+        let (primary_transform, primary_velocity, primary_acceleration, point_mass) = match primary
+        {
+            Some(primary) => primary,
+            None => {
+                leave_kepler_orbit(&mut commands, entity);
+                continue;
+            }
+        };
+
+        let (position, relative_velocity) =
+            kepler::propagate(orbit.position, orbit.velocity, G * point_mass.mass(), dt);
+        orbit.position = position;
+        orbit.velocity = relative_velocity;
+
+        // Matches the semi-implicit Euler step the physics engine applies to the primary.
+        let primary_velocity = primary_velocity.linear + primary_acceleration.linear * dt;
+        let primary_position = primary_transform.translation() + primary_velocity * dt;
+
+        transform.translation = (primary_position + position)
+            .truncate()
+            .extend(transform.translation.z);
+        velocity.linear = primary_velocity + relative_velocity;
+
+        orbit.steps += 1;
+        if orbit.steps >= kepler.check_interval {
+            orbit.steps = 0;
+            // Hysteresis avoids bodies repeatedly switching integrator on the edge of the tolerance.
+            if perturbation(primary_position + position, orbit.primary, &particle_set)
+                > 2.0 * kepler.tolerance
+            {
+                leave_kepler_orbit(&mut commands, entity);
+            }
+        }
+    }
+}
+
+fn kepler_window(
+    mut egui_ctx: ResMut<EguiContext>,
+    mut kepler: ResMut<KeplerMode>,
+    orbits: Query<(), With<KeplerOrbit>>,
+) {
+    Window::new("Integrator").show(egui_ctx.ctx_mut(), |ui| {
+        ui.checkbox(&mut kepler.enabled, "Kepler mode for massless bodies");
+        ui.add_enabled(
+            kepler.enabled,
+            Slider::new(&mut kepler.tolerance, 1E-5..=1E-1)
+                .logarithmic(true)
+                .text("Tolerance"),
+        );
+        ui.label(format!("{} bodies on Kepler orbits", orbits.iter().count()));
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn forecast_probe_completes_a_circular_orbit() {
+        let star = Body::new(Vec3::ZERO, G * 1E5, Entity::from_raw(0));
+        let mut forecast = Forecast::new([&star], |_| Vec3::ZERO);
+
+        let (radius, dt) = (1000.0, 1.0 / 60.0);
+        let speed = (star.mu / radius).sqrt();
+        let start = Vec3::new(radius, 0.0, 0.0);
+        let probe = forecast.add_probe(Entity::from_raw(1), start, Vec3::new(0.0, speed, 0.0));
+
+        let period = std::f32::consts::TAU * radius / speed;
+        for _ in 0..(period / dt).round() as usize {
+            forecast.step(dt);
+            let (position, _) = forecast.probe(probe);
+            assert!((position.length() / radius - 1.0).abs() < 1E-2);
+        }
+
+        assert!(forecast.probe(probe).0.distance(start) < 0.02 * radius);
+        let (star_position, star_velocity, _) = forecast.massive().next().unwrap();
+        assert_eq!((star_position, star_velocity), (Vec3::ZERO, Vec3::ZERO));
+    }
+}
@@ -0,0 +1,145 @@
+use std::f32::consts::TAU;
+
+use bevy::math::Vec3;
+
+/// Osculating elements of a two-body orbit, in the plane of the simulation.
+///
+/// Angles are measured counterclockwise from the world `x` axis.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct OrbitalElements {
+    pub semi_major_axis: f32,
+    pub eccentricity: f32,
+    pub argument_of_periapsis: f32,
+    pub true_anomaly: f32,
+    pub periapsis: f32,
+    pub apoapsis: Option<f32>,
+    pub period: Option<f32>,
+    pub specific_energy: f32,
+}
+
+impl OrbitalElements {
+    /// Computes the elements of a body at `position` moving at `velocity`, both relative to its primary.
+    ///
+    /// `mu` is the gravitational parameter of the pair, `G * (m1 + m2)`.
+    pub fn from_state(position: Vec3, velocity: Vec3, mu: f32) -> Self {
+        let distance = position.length();
+        let speed_squared = velocity.length_squared();
+
+        let angular_momentum = position.cross(velocity);
+        let eccentricity_vector =
+            ((speed_squared - mu / distance) * position - position.dot(velocity) * velocity) / mu;
+        let eccentricity = eccentricity_vector.length();
+
+        let specific_energy = 0.5 * speed_squared - mu / distance;
+        let semi_major_axis = -mu / (2.0 * specific_energy);
+        let periapsis = angular_momentum.length_squared() / (mu * (1.0 + eccentricity));
+
+        let argument_of_periapsis = if eccentricity > f32::EPSILON {
+            eccentricity_vector
+                .y
+                .atan2(eccentricity_vector.x)
+                .rem_euclid(TAU)
+        } else {
+            0.0
+        };
+
+        let true_anomaly = if eccentricity > f32::EPSILON {
+            let cos =
+                (eccentricity_vector.dot(position) / (eccentricity * distance)).clamp(-1.0, 1.0);
+            if position.dot(velocity) >= 0.0 {
+                cos.acos()
+            } else {
+                TAU - cos.acos()
+            }
+        } else {
+            position.y.atan2(position.x).rem_euclid(TAU)
+        };
+
+        let bound = specific_energy < 0.0;
+
+        Self {
+            semi_major_axis,
+            eccentricity,
+            argument_of_periapsis,
+            true_anomaly,
+            periapsis,
+            apoapsis: bound.then_some(semi_major_axis * (1.0 + eccentricity)),
+            period: bound.then_some(TAU * (semi_major_axis.powi(3) / mu).sqrt()),
+            specific_energy,
+        }
+    }
+
+    pub fn is_bound(&self) -> bool {
+        self.specific_energy < 0.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::FRAC_PI_2;
+
+    const MU: f32 = 1E5;
+
+    fn assert_close(a: f32, b: f32) {
+        assert!((a - b).abs() <= 1E-3 * b.abs().max(1.0), "{a} != {b}");
+    }
+
+    #[test]
+    fn circular_orbit() {
+        let radius = 200.0;
+        let speed = (MU / radius).sqrt();
+        let elements = OrbitalElements::from_state(
+            Vec3::new(radius, 0.0, 0.0),
+            Vec3::new(0.0, speed, 0.0),
+            MU,
+        );
+
+        assert!(elements.is_bound());
+        assert!(elements.eccentricity < 1E-3);
+        assert_close(elements.semi_major_axis, radius);
+        assert_close(elements.periapsis, radius);
+        assert_close(elements.apoapsis.unwrap(), radius);
+        assert_close(elements.period.unwrap(), TAU * (radius.powi(3) / MU).sqrt());
+    }
+
+    #[test]
+    fn elliptical_orbit_at_periapsis() {
+        let (periapsis, eccentricity) = (100.0, 0.5);
+        let speed = (MU * (1.0 + eccentricity) / periapsis).sqrt();
+        let elements = OrbitalElements::from_state(
+            Vec3::new(0.0, periapsis, 0.0),
+            Vec3::new(-speed, 0.0, 0.0),
+            MU,
+        );
+
+        assert!(elements.is_bound());
+        assert_close(elements.eccentricity, eccentricity);
+        assert_close(elements.periapsis, periapsis);
+        assert_close(elements.semi_major_axis, periapsis / (1.0 - eccentricity));
+        assert_close(
+            elements.apoapsis.unwrap(),
+            periapsis * (1.0 + eccentricity) / (1.0 - eccentricity),
+        );
+        assert_close(elements.argument_of_periapsis, FRAC_PI_2);
+        assert!(elements.true_anomaly < 1E-3 || elements.true_anomaly > TAU - 1E-3);
+    }
+
+    #[test]
+    fn hyperbolic_orbit_is_unbound() {
+        let distance = 100.0;
+        let escape_speed = (2.0 * MU / distance).sqrt();
+        let elements = OrbitalElements::from_state(
+            Vec3::new(distance, 0.0, 0.0),
+            Vec3::new(0.0, 1.5 * escape_speed, 0.0),
+            MU,
+        );
+
+        assert!(!elements.is_bound());
+        assert!(elements.eccentricity > 1.0);
+        assert!(elements.semi_major_axis < 0.0);
+        assert_close(elements.periapsis, distance);
+        assert_eq!(elements.apoapsis, None);
+        assert_eq!(elements.period, None);
+    }
+}
@@ -0,0 +1,100 @@
+use bevy::math::{DVec3, Vec3};
+
+const MAX_ITERATIONS: usize = 50;
+const TOLERANCE: f64 = 1E-10;
+
+// Stumpff functions C(z) and S(z).
+fn stumpff(z: f64) -> (f64, f64) {
+    if z > 1E-8 {
+        let sqrt = z.sqrt();
+        ((1.0 - sqrt.cos()) / z, (sqrt - sqrt.sin()) / (sqrt * z))
+    } else if z < -1E-8 {
+        let sqrt = (-z).sqrt();
+        ((sqrt.cosh() - 1.0) / -z, (sqrt.sinh() - sqrt) / (sqrt * -z))
+    } else {
+        (0.5, 1.0 / 6.0)
+    }
+}
+
+/// Advances a two-body state relative to its primary by `dt`, using universal variables.
+///
+/// Works for elliptical, parabolic and hyperbolic orbits alike.
+pub fn propagate(position: Vec3, velocity: Vec3, mu: f32, dt: f32) -> (Vec3, Vec3) {
+    let (r0, v0) = (position.as_dvec3(), velocity.as_dvec3());
+    let (mu, dt) = (mu as f64, dt as f64);
+    let sqrt_mu = mu.sqrt();
+
+    let r0_norm = r0.length();
+    let radial_velocity = r0.dot(v0) / r0_norm;
+    // Reciprocal of the semi-major axis.
+    let alpha = 2.0 / r0_norm - v0.length_squared() / mu;
+
+    let mut chi = sqrt_mu * alpha.abs() * dt;
+    for _ in 0..MAX_ITERATIONS {
+        let z = alpha * chi * chi;
+        let (c, s) = stumpff(z);
+        let f = r0_norm * radial_velocity / sqrt_mu * chi * chi * c
+            + (1.0 - alpha * r0_norm) * chi.powi(3) * s
+            + r0_norm * chi
+            - sqrt_mu * dt;
+        let df = r0_norm * radial_velocity / sqrt_mu * chi * (1.0 - z * s)
+            + (1.0 - alpha * r0_norm) * chi * chi * c
+            + r0_norm;
+
+        let step = f / df;
+        chi -= step;
+        if step.abs() < TOLERANCE {
+            break;
+        }
+    }
+
+    let z = alpha * chi * chi;
+    let (c, s) = stumpff(z);
+
+    let f = 1.0 - chi * chi / r0_norm * c;
+    let g = dt - chi.powi(3) * s / sqrt_mu;
+    let r: DVec3 = f * r0 + g * v0;
+    let r_norm = r.length();
+
+    let df = sqrt_mu / (r_norm * r0_norm) * (z * s - 1.0) * chi;
+    let dg = 1.0 - chi * chi / r_norm * c;
+    let v = df * r0 + dg * v0;
+
+    (r.as_vec3(), v.as_vec3())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::TAU;
+
+    const MU: f32 = 1E5;
+
+    #[test]
+    fn circular_orbit_returns_after_one_period() {
+        let radius = 300.0;
+        let position = Vec3::new(radius, 0.0, 0.0);
+        let velocity = Vec3::new(0.0, (MU / radius).sqrt(), 0.0);
+        let period = TAU * (radius.powi(3) / MU).sqrt();
+
+        let (quarter, _) = propagate(position, velocity, MU, period / 4.0);
+        assert!(quarter.distance(Vec3::new(0.0, radius, 0.0)) < 1E-2);
+
+        let (full, full_velocity) = propagate(position, velocity, MU, period);
+        assert!(full.distance(position) < 1E-2);
+        assert!(full_velocity.distance(velocity) < 1E-3);
+    }
+
+    #[test]
+    fn energy_is_conserved_on_hyperbolic_orbit() {
+        let position = Vec3::new(100.0, 50.0, 0.0);
+        let velocity = Vec3::new(-20.0, 60.0, 0.0);
+        let energy = |p: Vec3, v: Vec3| 0.5 * v.length_squared() - MU / p.length();
+
+        let (later, later_velocity) = propagate(position, velocity, MU, 5.0);
+
+        let (before, after) = (energy(position, velocity), energy(later, later_velocity));
+        assert!(before > 0.0);
+        assert!((before - after).abs() < 1E-3 * before.abs());
+    }
+}
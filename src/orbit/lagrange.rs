@@ -0,0 +1,117 @@
+use bevy::math::{Vec2, Vec3};
+
+/// Frame centred on the barycentre of a pair, rotating with the line joining them.
+#[derive(Clone, Copy, Debug)]
+pub struct PairFrame {
+    pub barycentre: Vec3,
+    pub velocity: Vec3,
+    pub axis: Vec3,
+    pub separation: f32,
+    pub angular_velocity: f32,
+}
+
+impl PairFrame {
+    /// Builds the frame of a primary and a secondary from their positions, velocities and masses.
+    pub fn new(primary: (Vec3, Vec3, f32), secondary: (Vec3, Vec3, f32)) -> Self {
+        let (p1, v1, m1) = primary;
+        let (p2, v2, m2) = secondary;
+        let total_mass = m1 + m2;
+
+        let relative_position = p2 - p1;
+        let separation = relative_position.length();
+
+        Self {
+            barycentre: (p1 * m1 + p2 * m2) / total_mass,
+            velocity: (v1 * m1 + v2 * m2) / total_mass,
+            axis: relative_position / separation,
+            separation,
+            angular_velocity: relative_position.cross(v2 - v1).z / (separation * separation),
+        }
+    }
+
+    /// Converts a point expressed in units of the separation into world coordinates.
+    pub fn to_world(self, point: Vec2) -> Vec3 {
+        let normal = Vec3::Z.cross(self.axis);
+        self.barycentre + (self.axis * point.x + normal * point.y) * self.separation
+    }
+
+    /// Velocity of a point at rest in the rotating frame.
+    pub fn velocity_at(self, position: Vec3) -> Vec3 {
+        self.velocity + Vec3::Z.cross(position - self.barycentre) * self.angular_velocity
+    }
+}
+
+/// Computes the five Lagrange points of a pair in its [`PairFrame`], in units of their separation.
+pub fn lagrange_points(primary_mass: f32, secondary_mass: f32) -> [Vec2; 5] {
+    let mu = (secondary_mass / (primary_mass + secondary_mass)) as f64;
+
+    // Net acceleration along the axis in the rotating frame, increasing on each interval between singularities.
+    let force = |x: f64| {
+        let (d1, d2) = (x + mu, x - 1.0 + mu);
+        x - (1.0 - mu) * d1 / d1.abs().powi(3) - mu * d2 / d2.abs().powi(3)
+    };
+
+    let bisect = |mut low: f64, mut high: f64| {
+        for _ in 0..64 {
+            let mid = 0.5 * (low + high);
+            if force(mid) < 0.0 {
+                low = mid;
+            } else {
+                high = mid;
+            }
+        }
+        (0.5 * (low + high)) as f32
+    };
+
+    const EPSILON: f64 = 1E-9;
+    let l1 = bisect(-mu + EPSILON, 1.0 - mu - EPSILON);
+    let l2 = bisect(1.0 - mu + EPSILON, 2.0);
+    let l3 = bisect(-2.0, -mu - EPSILON);
+
+    let x = 0.5 - mu as f32;
+    let y = 3.0_f32.sqrt() / 2.0;
+
+    [
+        Vec2::new(l1, 0.0),
+        Vec2::new(l2, 0.0),
+        Vec2::new(l3, 0.0),
+        Vec2::new(x, y),
+        Vec2::new(x, -y),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn collinear_points_match_hill_approximation() {
+        let (m1, m2) = (1E5_f32, 1.0);
+        let mu = m2 / (m1 + m2);
+        let hill = (mu / 3.0).cbrt();
+
+        let [l1, l2, l3, ..] = lagrange_points(m1, m2);
+
+        assert!((l1.x - (1.0 - mu - hill)).abs() < 1E-3);
+        assert!((l2.x - (1.0 - mu + hill)).abs() < 1E-3);
+        assert!((l3.x + 1.0).abs() < 1E-3);
+    }
+
+    #[test]
+    fn triangular_points_are_equidistant() {
+        let (m1, m2) = (10.0, 3.0);
+        let frame = PairFrame::new(
+            (Vec3::new(-3.0, 5.0, 0.0), Vec3::ZERO, m1),
+            (Vec3::new(7.0, -1.0, 0.0), Vec3::ZERO, m2),
+        );
+        let [.., l4, l5] = lagrange_points(m1, m2);
+
+        for point in [l4, l5] {
+            let world = frame.to_world(point);
+            let d1 = world.distance(Vec3::new(-3.0, 5.0, 0.0));
+            let d2 = world.distance(Vec3::new(7.0, -1.0, 0.0));
+            assert!((d1 - frame.separation).abs() < 1E-3);
+            assert!((d2 - frame.separation).abs() < 1E-3);
+        }
+    }
+}
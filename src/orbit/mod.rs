@@ -0,0 +1,73 @@
+mod elements;
+pub mod kepler;
+mod lagrange;
+mod spheres;
+mod systems;
+
+pub use elements::OrbitalElements;
+pub use lagrange::{lagrange_points, PairFrame};
+
+use bevy::{prelude::*, time::FixedTimestep, utils::HashMap};
+
+pub struct OrbitPlugin;
+
+impl Plugin for OrbitPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<OrbitOverlays>()
+            .init_resource::<Influences>()
+            .add_event::<SpawnTrojans>()
+            .add_system(systems::orbit_window)
+            .add_system(systems::update_influences.with_run_criteria(FixedTimestep::step(0.25)))
+            .add_system(systems::draw_influences)
+            .add_system(systems::label_primaries)
+            .add_system(systems::draw_lagrange_points)
+            .add_system(systems::spawn_trojans);
+    }
+}
+
+pub struct OrbitOverlays {
+    pub spheres: bool,
+    pub lagrange_points: bool,
+    /// Primaries are labelled on at most this many bodies on screen, the most massive first.
+    pub max_labels: usize,
+}
+
+impl Default for OrbitOverlays {
+    fn default() -> Self {
+        Self {
+            spheres: false,
+            lagrange_points: false,
+            max_labels: 64,
+        }
+    }
+}
+
+/// Spawns massless test particles at the L4 and L5 points of the selected pair.
+pub struct SpawnTrojans;
+
+/// The primary a body is bound to, and the Hill and Laplace spheres of massive bodies relative to it.
+pub struct InfluenceSpheres {
+    pub primary: Option<Entity>,
+    pub hill_radius: Option<f32>,
+    pub laplace_radius: Option<f32>,
+}
+
+/// Latest [`InfluenceSpheres`] of each body, empty while the overlay is hidden.
+#[derive(Default)]
+pub struct Influences(pub HashMap<Entity, InfluenceSpheres>);
+
+/// Finds the attractor exerting the strongest pull at `position` among `(entity, position, mass)` triples.
+pub fn dominant_attractor(
+    position: Vec3,
+    attractors: impl IntoIterator<Item = (Entity, Vec3, f32)>,
+) -> Option<(Entity, Vec3, f32)> {
+    attractors
+        .into_iter()
+        .filter(|(_, other, _)| *other != position)
+        .map(|attractor| {
+            let (_, other, mass) = attractor;
+            (attractor, mass / other.distance_squared(position))
+        })
+        .max_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(attractor, _)| attractor)
+}
@@ -0,0 +1,136 @@
+use bevy::math::Vec3;
+
+use super::OrbitalElements;
+use crate::G;
+
+/// Radius of the Hill sphere of a body of `mass` orbiting a primary of `primary_mass`.
+pub fn hill_radius(semi_major_axis: f32, eccentricity: f32, mass: f32, primary_mass: f32) -> f32 {
+    semi_major_axis * (1.0 - eccentricity) * (mass / (3.0 * primary_mass)).cbrt()
+}
+
+/// Radius of the Laplace sphere of influence of a body of `mass` orbiting a primary of `primary_mass`.
+pub fn laplace_radius(semi_major_axis: f32, mass: f32, primary_mass: f32) -> f32 {
+    semi_major_axis * (mass / primary_mass).powf(0.4)
+}
+
+#[derive(Clone, Copy)]
+pub struct HierarchyBody {
+    pub position: Vec3,
+    pub velocity: Vec3,
+    pub mass: f32,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Influence {
+    pub primary: Option<usize>,
+    pub hill_radius: Option<f32>,
+    pub laplace_radius: Option<f32>,
+}
+
+/// Assigns each body to the primary it is currently bound to.
+///
+/// Bodies are processed from the heaviest to the lightest: a body's primary is the heavier body with the
+/// smallest sphere of influence that contains it and to which it is gravitationally bound.
+/// Bodies without a primary have an unbounded sphere of influence.
+pub fn hierarchy(bodies: &[HierarchyBody]) -> Vec<Influence> {
+    let mut order: Vec<_> = (0..bodies.len()).collect();
+    order.sort_by(|&a, &b| bodies[b].mass.total_cmp(&bodies[a].mass));
+
+    let mut influences = vec![Influence::default(); bodies.len()];
+    let mut attractors: Vec<(usize, f32)> = Vec::new();
+
+    for index in order {
+        let body = bodies[index];
+
+        let primary = attractors
+            .iter()
+            .filter(|&&(other, radius)| {
+                let primary = bodies[other];
+                let position = body.position - primary.position;
+                let velocity = body.velocity - primary.velocity;
+                let mu = G * (body.mass + primary.mass);
+
+                primary.mass > body.mass
+                    && position.length() < radius
+                    && 0.5 * velocity.length_squared() < mu / position.length()
+            })
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|&(other, _)| other);
+
+        let influence = &mut influences[index];
+        influence.primary = primary;
+
+        if body.mass <= 0.0 {
+            continue;
+        }
+
+        let radius = if let Some(primary) = primary.map(|p| bodies[p]) {
+            let position = body.position - primary.position;
+            let elements = OrbitalElements::from_state(
+                position,
+                body.velocity - primary.velocity,
+                G * (body.mass + primary.mass),
+            );
+            let (semi_major_axis, eccentricity) = if elements.is_bound() {
+                (elements.semi_major_axis, elements.eccentricity)
+            } else {
+                (position.length(), 0.0)
+            };
+
+            influence.hill_radius = Some(hill_radius(
+                semi_major_axis,
+                eccentricity,
+                body.mass,
+                primary.mass,
+            ));
+            let radius = laplace_radius(semi_major_axis, body.mass, primary.mass);
+            influence.laplace_radius = Some(radius);
+            radius
+        } else {
+            f32::INFINITY
+        };
+
+        attractors.push((index, radius));
+    }
+
+    influences
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn circular(primary: HierarchyBody, distance: f32, mass: f32) -> HierarchyBody {
+        let speed = (G * (primary.mass + mass) / distance).sqrt();
+        HierarchyBody {
+            position: primary.position + Vec3::new(distance, 0.0, 0.0),
+            velocity: primary.velocity + Vec3::new(0.0, speed, 0.0),
+            mass,
+        }
+    }
+
+    #[test]
+    fn moon_is_bound_to_its_planet() {
+        let sun = HierarchyBody {
+            position: Vec3::ZERO,
+            velocity: Vec3::ZERO,
+            mass: 1E5,
+        };
+        let planet = circular(sun, 2000.0, 100.0);
+        let moon = circular(planet, 20.0, 0.0);
+        let comet = circular(sun, 500.0, 0.0);
+
+        let influences = hierarchy(&[moon, planet, sun, comet]);
+
+        assert_eq!(influences[0].primary, Some(1));
+        assert_eq!(influences[1].primary, Some(2));
+        assert_eq!(influences[2].primary, None);
+        assert_eq!(influences[3].primary, Some(2));
+
+        let laplace = influences[1].laplace_radius.unwrap();
+        let hill = influences[1].hill_radius.unwrap();
+        assert!(laplace > 20.0 && hill > 20.0);
+        assert!((hill - 2000.0 * (100.0 / 3E5_f32).cbrt()).abs() < 1.0);
+        assert_eq!(influences[0].laplace_radius, None);
+    }
+}
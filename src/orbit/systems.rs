@@ -0,0 +1,339 @@
+use bevy::prelude::*;
+use bevy_egui::{
+    egui::{self, Grid, Slider, Ui, Window},
+    EguiContext,
+};
+use bevy_mouse_tracking_plugin::MainCamera;
+use bevy_prototype_debug_lines::DebugLines;
+use heron::Velocity;
+
+use super::{
+    dominant_attractor, lagrange_points,
+    spheres::{hierarchy, HierarchyBody},
+    InfluenceSpheres, Influences, OrbitOverlays, OrbitalElements, PairFrame, SpawnTrojans,
+};
+use crate::{
+    nbody::PointMass,
+    selection::{body_label, Selection},
+    shapes::DebugShapes,
+    trails::Trail,
+    BodyBundle, LoadedScene, G,
+};
+
+pub fn orbit_window(
+    mut egui_ctx: ResMut<EguiContext>,
+    mut overlays: ResMut<OrbitOverlays>,
+    mut spawn_trojans: EventWriter<SpawnTrojans>,
+    selection: Res<Selection>,
+    bodies: Query<(
+        Entity,
+        &GlobalTransform,
+        &Velocity,
+        &PointMass,
+        Option<&Name>,
+    )>,
+) {
+    Window::new("Orbit").show(egui_ctx.ctx_mut(), |ui| {
+        ui.checkbox(&mut overlays.spheres, "Spheres of influence");
+        let spheres = overlays.spheres;
+        ui.add_enabled(
+            spheres,
+            Slider::new(&mut overlays.max_labels, 0..=256).text("Labelled primaries"),
+        );
+        ui.checkbox(&mut overlays.lagrange_points, "Lagrange points of pair");
+        if selection.pair().is_some() && ui.button("Spawn test particles at L4/L5").clicked() {
+            spawn_trojans.send(SpawnTrojans);
+        }
+        ui.separator();
+
+        let (entity, transform, velocity, point_mass, name) =
+            match selection.body().and_then(|e| bodies.get(e).ok()) {
+                Some(body) => body,
+                None => {
+                    ui.label("Click a body to select it.");
+                    ui.label("Shift+click another body to use it as primary.");
+                    return;
+                }
+            };
+        let position = transform.translation();
+
+        let primary = match selection.other() {
+            Some(other) => Some((other, false)),
+            None => {
+                let attractors = bodies
+                    .iter()
+                    .filter(|(e, .., p, _)| *e != entity && p.mass() > 0.0)
+                    .map(|(e, t, _, p, _)| (e, t.translation(), p.mass()));
+
+                dominant_attractor(position, attractors).map(|(e, ..)| (e, true))
+            }
+        };
+
+        ui.label(format!("Body: {}", body_label(entity, name)));
+
+        let (primary, dominant) = match primary {
+            Some(primary) => primary,
+            None => {
+                ui.label("No attractor.");
+                return;
+            }
+        };
+        // The primary may have been despawned since it was selected.
+        let Ok((_, primary_transform, primary_velocity, primary_mass, primary_name)) =
+            bodies.get(primary)
+        else {
+            ui.label("No attractor.");
+            return;
+        };
+
+        ui.label(format!(
+            "Primary: {}{}",
+            body_label(primary, primary_name),
+            if dominant { " (dominant)" } else { "" }
+        ));
+
+        let mu = G * (point_mass.mass() + primary_mass.mass());
+        if mu <= 0.0 {
+            ui.label("Neither body has mass.");
+            return;
+        }
+
+        let elements = OrbitalElements::from_state(
+            position - primary_transform.translation(),
+            velocity.linear - primary_velocity.linear,
+            mu,
+        );
+
+        ui.separator();
+        show_elements(ui, &elements);
+    });
+}
+
+fn show_elements(ui: &mut Ui, elements: &OrbitalElements) {
+    let or_infinite = |value: Option<f32>| value.map_or("∞".to_string(), |v| format!("{v:.1}"));
+
+    Grid::new("orbital_elements")
+        .num_columns(2)
+        .striped(true)
+        .show(ui, |ui| {
+            ui.label("Semi-major axis");
+            ui.label(format!("{:.1}", elements.semi_major_axis));
+            ui.end_row();
+
+            ui.label("Eccentricity");
+            ui.label(format!("{:.4}", elements.eccentricity));
+            ui.end_row();
+
+            ui.label("Argument of periapsis");
+            ui.label(format!(
+                "{:.1}°",
+                elements.argument_of_periapsis.to_degrees()
+            ));
+            ui.end_row();
+
+            ui.label("Period");
+            ui.label(or_infinite(elements.period));
+            ui.end_row();
+
+            ui.label("Periapsis");
+            ui.label(format!("{:.1}", elements.periapsis));
+            ui.end_row();
+
+            ui.label("Apoapsis");
+            ui.label(or_infinite(elements.apoapsis));
+            ui.end_row();
+
+            ui.label("Bound");
+            ui.label(if elements.is_bound() { "Yes" } else { "No" });
+            ui.end_row();
+        });
+}
+
+pub fn update_influences(
+    overlays: Res<OrbitOverlays>,
+    mut influences: ResMut<Influences>,
+    bodies: Query<(Entity, &GlobalTransform, &Velocity, &PointMass)>,
+) {
+    if !overlays.spheres {
+        if !influences.0.is_empty() {
+            influences.0.clear();
+        }
+        return;
+    }
+
+    let (entities, bodies): (Vec<_>, Vec<_>) = bodies
+        .iter()
+        .map(|(entity, transform, velocity, point_mass)| {
+            let body = HierarchyBody {
+                position: transform.translation(),
+                velocity: velocity.linear,
+                mass: point_mass.mass(),
+            };
+            (entity, body)
+        })
+        .unzip();
+
+    influences.0 = entities
+        .iter()
+        .zip(hierarchy(&bodies))
+        .map(|(entity, influence)| {
+            let spheres = InfluenceSpheres {
+                primary: influence.primary.map(|index| entities[index]),
+                hill_radius: influence.hill_radius,
+                laplace_radius: influence.laplace_radius,
+            };
+            (*entity, spheres)
+        })
+        .collect();
+}
+
+pub fn draw_influences(
+    overlays: Res<OrbitOverlays>,
+    influences: Res<Influences>,
+    mut lines: ResMut<DebugLines>,
+    bodies: Query<(&GlobalTransform, &Sprite)>,
+) {
+    if !overlays.spheres {
+        return;
+    }
+
+    // Bodies despawned since the last update are skipped.
+    for (entity, influence) in influences.0.iter() {
+        let position = match bodies.get(*entity) {
+            Ok((transform, _)) => transform.translation(),
+            Err(_) => continue,
+        };
+
+        if let Some(radius) = influence.hill_radius {
+            lines.circle(position, radius, Color::rgba(0.2, 0.8, 0.2, 0.6));
+        }
+        if let Some(radius) = influence.laplace_radius {
+            lines.circle(position, radius, Color::rgba(0.2, 0.4, 1.0, 0.6));
+        }
+
+        if let Some((primary, sprite)) = influence.primary.and_then(|e| bodies.get(e).ok()) {
+            let mut color = sprite.color;
+            color.set_a(0.15);
+            lines.line_colored(position, primary.translation(), 0.0, color);
+        }
+    }
+}
+
+pub fn label_primaries(
+    mut egui_ctx: ResMut<EguiContext>,
+    overlays: Res<OrbitOverlays>,
+    influences: Res<Influences>,
+    camera: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+    bodies: Query<(&GlobalTransform, &PointMass)>,
+    names: Query<Option<&Name>>,
+) {
+    let (camera, camera_transform) = match camera.get_single() {
+        Ok(camera) if overlays.spheres => camera,
+        _ => return,
+    };
+    let size = camera.logical_viewport_size().unwrap_or_default();
+
+    let mut labels: Vec<_> = influences
+        .0
+        .iter()
+        .filter_map(|(entity, influence)| {
+            let primary = influence.primary?;
+            let (transform, point_mass) = bodies.get(*entity).ok()?;
+            let position = camera.world_to_viewport(camera_transform, transform.translation())?;
+            let visible = position.cmpge(Vec2::ZERO).all() && position.cmple(size).all();
+            visible.then_some((position, primary, point_mass.mass()))
+        })
+        .collect();
+    labels.sort_by(|a, b| b.2.total_cmp(&a.2));
+    labels.truncate(overlays.max_labels);
+
+    let painter = egui_ctx
+        .ctx_mut()
+        .layer_painter(egui::LayerId::background());
+    for (position, primary, _) in labels {
+        painter.text(
+            egui::pos2(position.x + 4.0, size.y - position.y),
+            egui::Align2::LEFT_BOTTOM,
+            body_label(primary, names.get(primary).ok().flatten()),
+            egui::FontId::proportional(12.0),
+            egui::Color32::GRAY,
+        );
+    }
+}
+
+fn selected_pair(
+    selection: &Selection,
+    bodies: &Query<(&GlobalTransform, &Velocity, &PointMass)>,
+) -> Option<(PairFrame, [f32; 2])> {
+    let (body, other) = selection.pair()?;
+    let state = |e| {
+        bodies
+            .get(e)
+            .ok()
+            .map(|(t, v, p)| (t.translation(), v.linear, p.mass()))
+    };
+    let (mut primary, mut secondary) = (state(body)?, state(other)?);
+
+    if secondary.2 > primary.2 {
+        std::mem::swap(&mut primary, &mut secondary);
+    }
+    if primary.2 <= 0.0 {
+        return None;
+    }
+
+    Some((PairFrame::new(primary, secondary), [primary.2, secondary.2]))
+}
+
+pub fn draw_lagrange_points(
+    overlays: Res<OrbitOverlays>,
+    selection: Res<Selection>,
+    mut lines: ResMut<DebugLines>,
+    camera: Query<&OrthographicProjection, With<MainCamera>>,
+    bodies: Query<(&GlobalTransform, &Velocity, &PointMass)>,
+) {
+    if !overlays.lagrange_points {
+        return;
+    }
+
+    if let Some((frame, [primary_mass, secondary_mass])) = selected_pair(&selection, &bodies) {
+        let size = 5.0 * camera.get_single().map_or(1.0, |proj| proj.scale);
+        for point in lagrange_points(primary_mass, secondary_mass) {
+            lines.cross(frame.to_world(point), size, Color::FUCHSIA);
+        }
+    }
+}
+
+pub fn spawn_trojans(
+    mut commands: Commands,
+    mut events: EventReader<SpawnTrojans>,
+    selection: Res<Selection>,
+    scene: Res<LoadedScene>,
+    bodies: Query<(&GlobalTransform, &Velocity, &PointMass)>,
+    asset_server: Res<AssetServer>,
+) {
+    for _ in events.iter() {
+        let (frame, [primary_mass, secondary_mass]) = match selected_pair(&selection, &bodies) {
+            Some(pair) => pair,
+            None => continue,
+        };
+        let [.., l4, l5] = lagrange_points(primary_mass, secondary_mass);
+        let (density, physics_mass, point_mass) = scene.spawnable().body(None);
+
+        commands.entity(scene.entity()).with_children(|child| {
+            for point in [l4, l5] {
+                let position = frame.to_world(point);
+                child
+                    .spawn_bundle(BodyBundle::new(
+                        position,
+                        Velocity::from_linear(frame.velocity_at(position)),
+                        density,
+                        physics_mass,
+                        point_mass,
+                        Color::WHITE,
+                        &asset_server,
+                    ))
+                    .insert(Trail::new(20.0));
+            }
+        });
+    }
+}
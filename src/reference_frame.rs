@@ -0,0 +1,301 @@
+use std::f32::consts::{PI, TAU};
+
+use bevy::{prelude::*, transform::TransformSystem};
+use bevy_egui::{
+    egui::{Checkbox, ComboBox, Window},
+    EguiContext,
+};
+use bevy_mouse_tracking_plugin::MainCamera;
+use heron::{PhysicsSystem, Velocity};
+
+use crate::{
+    nbody::PointMass,
+    orbit::PairFrame,
+    selection::{body_label, Selection},
+};
+
+pub struct ReferenceFramePlugin;
+
+impl Plugin for ReferenceFramePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ReferenceFrame>()
+            .init_resource::<FrameTransform>()
+            .init_resource::<FrameView>()
+            .add_system(frame_window)
+            .add_system(follow_spawned)
+            .add_system_to_stage(
+                CoreStage::PostUpdate,
+                update_frame.after(PhysicsSystem::TransformUpdate),
+            )
+            .add_system_to_stage(
+                CoreStage::PostUpdate,
+                follow_frame
+                    .after(update_frame)
+                    .before(TransformSystem::TransformPropagate),
+            );
+    }
+}
+
+/// The frame in which the view, trails and placed bodies are expressed.
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+pub enum ReferenceFrame {
+    #[default]
+    World,
+    Barycentric,
+    Body(Entity),
+    CoRotating(Entity, Entity),
+}
+
+impl ReferenceFrame {
+    /// Follows `new` in place of `old`, a body that was respawned.
+    pub fn remap(&mut self, old: Entity, new: Entity) {
+        let swap = |entity: &mut Entity| {
+            if *entity == old {
+                *entity = new;
+            }
+        };
+        match self {
+            ReferenceFrame::World | ReferenceFrame::Barycentric => {}
+            ReferenceFrame::Body(entity) => swap(entity),
+            ReferenceFrame::CoRotating(a, b) => {
+                swap(a);
+                swap(b);
+            }
+        }
+    }
+}
+
+/// Centres the [`ReferenceFrame`] on a body once it is spawned, for scenes that follow one of their
+/// bodies.
+#[derive(Component)]
+pub struct FollowOnSpawn;
+
+/// How the current [`ReferenceFrame`] is displayed.
+#[derive(Default)]
+pub struct FrameView {
+    /// Rotates the view with co-rotating frames, so that the pair stays fixed on screen.
+    pub rotate: bool,
+}
+
+/// Motion of the current [`ReferenceFrame`] in world coordinates.
+///
+/// Transforms hold the state of the physics simulation, so the view of the frame is applied to the
+/// camera and the trails rather than to the bodies themselves.
+#[derive(Clone, Copy, Default)]
+pub struct FrameTransform {
+    pub origin: Vec3,
+    pub velocity: Vec3,
+    /// Orientation of the frame axes in the view, zero unless the view rotates with the frame.
+    pub angle: f32,
+    pub angular_velocity: f32,
+    /// Displacement of the origin since the last update.
+    pub displacement: Vec3,
+    /// Rotation of the view since the last update.
+    pub rotation: f32,
+}
+
+impl FrameTransform {
+    /// World velocity of a point at rest in the frame.
+    pub fn velocity_at(&self, position: Vec3) -> Vec3 {
+        self.velocity + Vec3::Z.cross(position - self.origin) * self.angular_velocity
+    }
+
+    /// Moves a point drawn during the last update along with the displayed frame.
+    pub fn reanchor(&self, position: Vec3) -> Vec3 {
+        let previous_origin = self.origin - self.displacement;
+        self.origin + Quat::from_rotation_z(self.rotation) * (position - previous_origin)
+    }
+}
+
+struct FrameMotion {
+    origin: Vec3,
+    velocity: Vec3,
+    angle: f32,
+    angular_velocity: f32,
+}
+
+impl FrameMotion {
+    const WORLD: Self = Self {
+        origin: Vec3::ZERO,
+        velocity: Vec3::ZERO,
+        angle: 0.0,
+        angular_velocity: 0.0,
+    };
+
+    fn translating(origin: Vec3, velocity: Vec3) -> Self {
+        Self {
+            origin,
+            velocity,
+            ..Self::WORLD
+        }
+    }
+}
+
+pub type Bodies<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static GlobalTransform,
+        &'static Velocity,
+        &'static PointMass,
+    ),
+>;
+
+fn frame_motion(frame: ReferenceFrame, bodies: &Bodies) -> Option<FrameMotion> {
+    match frame {
+        ReferenceFrame::World => Some(FrameMotion::WORLD),
+        ReferenceFrame::Barycentric => {
+            let (moment, momentum, mass) = bodies.iter().fold(
+                (Vec3::ZERO, Vec3::ZERO, 0.0),
+                |(moment, momentum, total), (transform, velocity, point_mass)| {
+                    let mass = point_mass.mass();
+                    (
+                        moment + transform.translation() * mass,
+                        momentum + velocity.linear * mass,
+                        total + mass,
+                    )
+                },
+            );
+            (mass > 0.0).then(|| FrameMotion::translating(moment / mass, momentum / mass))
+        }
+        ReferenceFrame::Body(entity) => {
+            let (transform, velocity, _) = bodies.get(entity).ok()?;
+            Some(FrameMotion::translating(
+                transform.translation(),
+                velocity.linear,
+            ))
+        }
+        ReferenceFrame::CoRotating(a, b) => {
+            let state = |e| {
+                bodies
+                    .get(e)
+                    .ok()
+                    .map(|(t, v, p)| (t.translation(), v.linear, p.mass()))
+            };
+            let (a, b) = (state(a)?, state(b)?);
+            let pair = PairFrame::new(a, b);
+            (a.2 + b.2 > 0.0).then(|| FrameMotion {
+                origin: pair.barycentre,
+                velocity: pair.velocity,
+                angle: pair.axis.y.atan2(pair.axis.x),
+                angular_velocity: pair.angular_velocity,
+            })
+        }
+    }
+}
+
+/// Current origin of `frame`, if its bodies still exist.
+pub fn frame_origin(frame: ReferenceFrame, bodies: &Bodies) -> Option<Vec3> {
+    frame_motion(frame, bodies).map(|motion| motion.origin)
+}
+
+pub fn update_frame(
+    mut frame: ResMut<ReferenceFrame>,
+    mut transform: ResMut<FrameTransform>,
+    view: Res<FrameView>,
+    bodies: Bodies,
+) {
+    let motion = frame_motion(*frame, &bodies).unwrap_or_else(|| {
+        *frame = ReferenceFrame::World;
+        FrameMotion::WORLD
+    });
+    let angle = if view.rotate { motion.angle } else { 0.0 };
+    let reset = frame.is_changed() || view.is_changed();
+
+    *transform = FrameTransform {
+        origin: motion.origin,
+        velocity: motion.velocity,
+        angle,
+        angular_velocity: motion.angular_velocity,
+        displacement: if reset {
+            Vec3::ZERO
+        } else {
+            motion.origin - transform.origin
+        },
+        rotation: if reset {
+            0.0
+        } else {
+            (angle - transform.angle + PI).rem_euclid(TAU) - PI
+        },
+    };
+}
+
+fn follow_spawned(mut frame: ResMut<ReferenceFrame>, spawned: Query<Entity, Added<FollowOnSpawn>>) {
+    if let Some(entity) = spawned.iter().last() {
+        *frame = ReferenceFrame::Body(entity);
+    }
+}
+
+fn follow_frame(
+    frame: Res<ReferenceFrame>,
+    view: Res<FrameView>,
+    transform: Res<FrameTransform>,
+    mut camera: Query<&mut Transform, With<MainCamera>>,
+) {
+    for mut camera in camera.iter_mut() {
+        if frame.is_changed() || view.is_changed() {
+            camera.translation = transform.origin.truncate().extend(camera.translation.z);
+            camera.rotation = Quat::from_rotation_z(transform.angle);
+        } else {
+            let z = camera.translation.z;
+            camera.translation = transform.reanchor(camera.translation).truncate().extend(z);
+            camera.rotation = Quat::from_rotation_z(transform.rotation) * camera.rotation;
+        }
+    }
+}
+
+fn frame_window(
+    mut egui_ctx: ResMut<EguiContext>,
+    mut frame: ResMut<ReferenceFrame>,
+    mut view: ResMut<FrameView>,
+    selection: Res<Selection>,
+    names: Query<Option<&Name>>,
+) {
+    let label = |entity: Entity| body_label(entity, names.get(entity).ok().flatten());
+    let describe = |frame: ReferenceFrame| match frame {
+        ReferenceFrame::World => "World".to_string(),
+        ReferenceFrame::Barycentric => "Barycentric".to_string(),
+        ReferenceFrame::Body(entity) => format!("Centred on {}", label(entity)),
+        ReferenceFrame::CoRotating(a, b) => format!("Co-rotating {} & {}", label(a), label(b)),
+    };
+
+    Window::new("Reference frame").show(egui_ctx.ctx_mut(), |ui| {
+        let mut selected = *frame;
+        ComboBox::from_label("")
+            .selected_text(describe(selected))
+            .show_ui(ui, |ui| {
+                let mut options = vec![ReferenceFrame::World, ReferenceFrame::Barycentric];
+                options.extend(selection.body().map(ReferenceFrame::Body));
+                options.extend(
+                    selection
+                        .pair()
+                        .map(|(a, b)| ReferenceFrame::CoRotating(a, b)),
+                );
+                if !options.contains(&selected) {
+                    options.push(selected);
+                }
+
+                for option in options {
+                    ui.selectable_value(&mut selected, option, describe(option));
+                }
+            });
+
+        if selected != *frame {
+            *frame = selected;
+        }
+
+        let rotating = matches!(*frame, ReferenceFrame::CoRotating(..));
+        let mut rotate = view.rotate;
+        ui.add_enabled(
+            rotating,
+            Checkbox::new(&mut rotate, "Rotate view with frame"),
+        );
+        if rotate != view.rotate {
+            view.rotate = rotate;
+        }
+
+        if selection.body().is_none() {
+            ui.label("Select a body, or a pair with shift+click, for more frames.");
+        }
+    });
+}
@@ -0,0 +1,172 @@
+use bevy::input::mouse::MouseButtonInput;
+use bevy::input::ButtonState;
+use bevy::prelude::*;
+use bevy_egui::EguiContext;
+use bevy_mouse_tracking_plugin::{MainCamera, MousePosWorld};
+use bevy_prototype_debug_lines::DebugLines;
+use heron::CollisionShape;
+
+use crate::{nbody::PointMass, shapes::DebugShapes};
+
+// Minimum radius in pixels under which a body can still be picked.
+const PICK_RADIUS: f32 = 6.0;
+
+pub struct SelectionPlugin;
+
+impl Plugin for SelectionPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Selection>()
+            .init_resource::<PointerCapture>()
+            .add_system_to_stage(CoreStage::PreUpdate, hover_body)
+            .add_system(select_body)
+            .add_system(draw_selection);
+    }
+}
+
+/// The selected body and an optional second body, used as a primary or to form a pair.
+#[derive(Default)]
+pub struct Selection {
+    body: Option<Entity>,
+    other: Option<Entity>,
+    hovered: Option<Entity>,
+}
+
+impl Selection {
+    pub fn body(&self) -> Option<Entity> {
+        self.body
+    }
+
+    pub fn other(&self) -> Option<Entity> {
+        self.other
+    }
+
+    pub fn pair(&self) -> Option<(Entity, Entity)> {
+        self.body.zip(self.other)
+    }
+
+    pub fn hovered(&self) -> Option<Entity> {
+        self.hovered
+    }
+
+    pub fn select(&mut self, entity: Entity) {
+        if self.other == Some(entity) {
+            self.other = None;
+        }
+        self.body = Some(entity);
+    }
+
+    pub fn select_other(&mut self, entity: Entity) {
+        if self.body.is_none() {
+            self.body = Some(entity);
+        } else if self.body != Some(entity) {
+            self.other = Some(entity);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.body = None;
+        self.other = None;
+    }
+
+    /// Selects `new` in place of `old`, a body that was respawned.
+    pub fn remap(&mut self, old: Entity, new: Entity) {
+        for entity in [&mut self.body, &mut self.other].into_iter().flatten() {
+            if *entity == old {
+                *entity = new;
+            }
+        }
+    }
+}
+
+/// Set while the pointer is over a control drawn in the world, such as a manoeuvre handle, so that
+/// clicks neither select nor place bodies.
+#[derive(Default)]
+pub struct PointerCapture(pub bool);
+
+pub fn body_label(entity: Entity, name: Option<&Name>) -> String {
+    name.map_or_else(|| format!("Body {}", entity.id()), |name| name.to_string())
+}
+
+fn hover_body(
+    mut selection: ResMut<Selection>,
+    mut egui_ctx: ResMut<EguiContext>,
+    mouse_pos: Res<MousePosWorld>,
+    camera: Query<&OrthographicProjection, With<MainCamera>>,
+    bodies: Query<(Entity, &GlobalTransform, &CollisionShape), With<PointMass>>,
+) {
+    if selection.body.is_some_and(|e| !bodies.contains(e)) {
+        selection.body = selection.other.take();
+    }
+    if selection.other.is_some_and(|e| !bodies.contains(e)) {
+        selection.other = None;
+    }
+
+    if egui_ctx.ctx_mut().wants_pointer_input() {
+        selection.hovered = None;
+        return;
+    }
+
+    let min_radius = PICK_RADIUS * camera.get_single().map_or(1.0, |proj| proj.scale);
+    let mouse_pos = mouse_pos.truncate().extend(0.0);
+
+    selection.hovered = bodies
+        .iter()
+        .filter_map(|(entity, transform, shape)| {
+            let radius = match shape {
+                CollisionShape::Sphere { radius } => radius.max(min_radius),
+                _ => min_radius,
+            };
+            let distance = transform.translation().distance(mouse_pos);
+            (distance <= radius).then_some((entity, distance))
+        })
+        .min_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(entity, _)| entity);
+}
+
+fn select_body(
+    mut selection: ResMut<Selection>,
+    mut click_event: EventReader<MouseButtonInput>,
+    keys: Res<Input<KeyCode>>,
+    capture: Res<PointerCapture>,
+) {
+    if keys.just_pressed(KeyCode::Escape) {
+        selection.clear();
+    }
+
+    for event in click_event.iter() {
+        if capture.0 {
+            continue;
+        }
+        if event.button == MouseButton::Left && event.state == ButtonState::Pressed {
+            if let Some(hovered) = selection.hovered {
+                if keys.any_pressed([KeyCode::LShift, KeyCode::RShift]) {
+                    selection.select_other(hovered);
+                } else {
+                    selection.select(hovered);
+                }
+            }
+        }
+    }
+}
+
+fn draw_selection(
+    selection: Res<Selection>,
+    mut lines: ResMut<DebugLines>,
+    camera: Query<&OrthographicProjection, With<MainCamera>>,
+    bodies: Query<(&GlobalTransform, &CollisionShape)>,
+) {
+    let margin = PICK_RADIUS * camera.get_single().map_or(1.0, |proj| proj.scale);
+
+    let mut highlight = |entity: Option<Entity>, color: Color| {
+        if let Some((transform, shape)) = entity.and_then(|e| bodies.get(e).ok()) {
+            let radius = match shape {
+                CollisionShape::Sphere { radius } => *radius,
+                _ => 0.0,
+            };
+            lines.circle(transform.translation(), radius + margin, color);
+        }
+    };
+
+    highlight(selection.body, Color::YELLOW);
+    highlight(selection.other, Color::CYAN);
+}
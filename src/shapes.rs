@@ -0,0 +1,31 @@
+use std::f32::consts::TAU;
+
+use bevy::prelude::*;
+use bevy_prototype_debug_lines::DebugLines;
+
+const CIRCLE_SEGMENTS: usize = 48;
+
+pub trait DebugShapes {
+    fn circle(&mut self, center: Vec3, radius: f32, color: Color);
+
+    fn cross(&mut self, center: Vec3, size: f32, color: Color);
+}
+
+impl DebugShapes for DebugLines {
+    fn circle(&mut self, center: Vec3, radius: f32, color: Color) {
+        let point = |i: usize| {
+            let theta = TAU * i as f32 / CIRCLE_SEGMENTS as f32;
+            center + Vec3::new(theta.cos(), theta.sin(), 0.0) * radius
+        };
+
+        for i in 0..CIRCLE_SEGMENTS {
+            self.line_colored(point(i), point(i + 1), 0.0, color);
+        }
+    }
+
+    fn cross(&mut self, center: Vec3, size: f32, color: Color) {
+        let (x, y) = (Vec3::X * size, Vec3::Y * size);
+        self.line_colored(center - x - y, center + x + y, 0.0, color);
+        self.line_colored(center - x + y, center + x - y, 0.0, color);
+    }
+}
@@ -0,0 +1,114 @@
+use super::{SceneData, SimulationScene};
+
+/// How a scene is listed in the Simulation window.
+#[derive(Clone)]
+pub struct SceneInfo {
+    /// Identifies the scene across renames, in snapshots and in the loaded scene.
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub category: String,
+}
+
+impl SceneInfo {
+    pub fn new(id: impl Into<String>, name: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            name: name.into(),
+            description: String::new(),
+            category: "Other".to_string(),
+        }
+    }
+
+    pub fn with_description(mut self, description: impl Into<String>) -> Self {
+        self.description = description.into();
+        self
+    }
+
+    pub fn with_category(mut self, category: impl Into<String>) -> Self {
+        self.category = category.into();
+        self
+    }
+}
+
+pub struct RegisteredScene {
+    pub info: SceneInfo,
+    pub scene: SimulationScene,
+}
+
+/// The scenes that can be started from the Simulation window, looked up by id.
+#[derive(Default)]
+pub struct SceneCollection {
+    scenes: Vec<RegisteredScene>,
+}
+
+impl SceneCollection {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_scene<S>(mut self, info: SceneInfo) -> Self
+    where
+        S: SceneData + Default + Send + Sync + 'static,
+    {
+        self.insert(info, Box::new(S::default()));
+        self
+    }
+
+    /// Adds a scene, replacing the one with the same id if any.
+    pub fn insert(&mut self, info: SceneInfo, scene: SimulationScene) {
+        let registered = RegisteredScene { info, scene };
+        match self.position(&registered.info.id) {
+            Some(i) => self.scenes[i] = registered,
+            None => self.scenes.push(registered),
+        }
+    }
+
+    pub fn get(&self, id: &str) -> Option<&RegisteredScene> {
+        self.scenes.iter().find(|s| s.info.id == id)
+    }
+
+    pub fn get_mut(&mut self, id: &str) -> Option<&mut RegisteredScene> {
+        self.scenes.iter_mut().find(|s| s.info.id == id)
+    }
+
+    pub fn position(&self, id: &str) -> Option<usize> {
+        self.scenes.iter().position(|s| s.info.id == id)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &RegisteredScene> {
+        self.scenes.iter()
+    }
+
+    /// Categories in the order their first scene was registered.
+    pub fn categories(&self) -> Vec<&str> {
+        let mut categories: Vec<&str> = Vec::new();
+        for scene in &self.scenes {
+            if !categories.contains(&scene.info.category.as_str()) {
+                categories.push(&scene.info.category);
+            }
+        }
+        categories
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulation_scene::Empty;
+
+    #[test]
+    fn scenes_are_found_and_replaced_by_id() {
+        let mut scenes = SceneCollection::new()
+            .with_scene::<Empty>(SceneInfo::new("a", "A").with_category("Two"))
+            .with_scene::<Empty>(SceneInfo::new("b", "B").with_category("One"))
+            .with_scene::<Empty>(SceneInfo::new("c", "C").with_category("Two"));
+
+        scenes.insert(SceneInfo::new("b", "Renamed"), Box::new(Empty));
+
+        assert_eq!(scenes.position("b"), Some(1));
+        assert_eq!(scenes.get("b").unwrap().info.name, "Renamed");
+        assert!(scenes.get("d").is_none());
+        assert_eq!(scenes.categories(), ["Two", "Other"]);
+    }
+}
@@ -0,0 +1,541 @@
+use std::{f32::consts::TAU, fmt::Display, sync::Arc};
+
+use bevy::{
+    asset::{AssetLoader, BoxedFuture, LoadContext, LoadedAsset},
+    ecs::system::EntityCommands,
+    prelude::*,
+    reflect::TypeUuid,
+};
+use bevy_egui::egui::Ui;
+use heron::Velocity;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use serde::Deserialize;
+
+use super::{
+    seed_picker, LoadedScene, SceneCollection, SceneData, SceneInfo, SceneParams, Spawnable,
+};
+use crate::{nbody::PointMass, trails::Trail, BodyBundle, G};
+
+/// A scene described by a `.sim.ron` or `.sim.json` file.
+#[derive(Clone, Deserialize, TypeUuid)]
+#[uuid = "5b1f6a2e-3f0c-4d7e-9a8c-2f4b7d61c0e9"]
+pub struct SceneFile {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    /// Where the scene is listed, "Files" by default.
+    #[serde(default)]
+    pub category: Option<String>,
+    #[serde(default = "default_spawnable")]
+    pub spawnable: Spawnable,
+    #[serde(default)]
+    pub bodies: Vec<BodyDescription>,
+    #[serde(default)]
+    pub generators: Vec<Generator>,
+    /// Seed of the generators, which can be changed in the Simulation window.
+    #[serde(default)]
+    pub seed: u64,
+}
+
+fn default_spawnable() -> Spawnable {
+    Spawnable::Massive {
+        min_mass: 1.0,
+        max_mass: 100.0,
+        density: 0.1,
+    }
+}
+
+#[derive(Clone, Deserialize)]
+#[serde(default)]
+pub struct BodyDescription {
+    pub name: Option<String>,
+    pub position: [f32; 2],
+    pub velocity: [f32; 2],
+    pub mass: f32,
+    pub density: f32,
+    pub color: [f32; 3],
+    /// Length of the trail in seconds, without a trail if missing.
+    pub trail: Option<f32>,
+    pub massless: bool,
+}
+
+impl Default for BodyDescription {
+    fn default() -> Self {
+        Self {
+            name: None,
+            position: [0.0; 2],
+            velocity: [0.0; 2],
+            mass: 10.0,
+            density: 0.1,
+            color: [1.0; 3],
+            trail: None,
+            massless: false,
+        }
+    }
+}
+
+/// Groups of bodies built from a template, whose position and velocity are ignored.
+///
+/// With `orbiting`, the index of a body in the file, bodies are centred on that body and put on
+/// circular orbits around it.
+#[derive(Clone, Deserialize)]
+pub enum Generator {
+    /// Evenly spaced bodies on a circle.
+    Ring {
+        #[serde(default)]
+        center: [f32; 2],
+        #[serde(default)]
+        orbiting: Option<usize>,
+        radius: f32,
+        count: usize,
+        #[serde(default)]
+        body: BodyDescription,
+    },
+    /// Bodies spread uniformly over an annulus.
+    Disk {
+        #[serde(default)]
+        center: [f32; 2],
+        #[serde(default)]
+        orbiting: Option<usize>,
+        inner_radius: f32,
+        outer_radius: f32,
+        count: usize,
+        #[serde(default)]
+        body: BodyDescription,
+    },
+    /// Bodies spread uniformly over a disk, with random velocities around a common one.
+    Cloud {
+        #[serde(default)]
+        center: [f32; 2],
+        #[serde(default)]
+        velocity: [f32; 2],
+        radius: f32,
+        #[serde(default)]
+        velocity_dispersion: f32,
+        count: usize,
+        #[serde(default)]
+        body: BodyDescription,
+    },
+}
+
+/// A body ready to be spawned.
+#[derive(Clone)]
+pub struct BodySpawn {
+    pub name: Option<String>,
+    pub position: Vec3,
+    pub velocity: Vec3,
+    pub density: f32,
+    pub physics_mass: f32,
+    pub point_mass: PointMass,
+    pub color: Color,
+    pub trail: Option<f32>,
+}
+
+impl BodyDescription {
+    fn spawn(&self, name: Option<String>, position: Vec3, velocity: Vec3) -> BodySpawn {
+        let (density, physics_mass, point_mass) = if self.massless {
+            (self.density, 0.01, PointMass::AffectedByGravity)
+        } else {
+            (
+                self.density,
+                self.mass,
+                PointMass::HasGravity { mass: self.mass },
+            )
+        };
+        let [r, g, b] = self.color;
+
+        BodySpawn {
+            name,
+            position,
+            velocity,
+            density,
+            physics_mass,
+            point_mass,
+            color: Color::rgb(r, g, b),
+            trail: self.trail,
+        }
+    }
+}
+
+fn vec3([x, y]: [f32; 2]) -> Vec3 {
+    Vec3::new(x, y, 0.0)
+}
+
+/// A generator of a scene file whose parameters cannot be sampled.
+#[derive(Debug)]
+pub struct InvalidGenerator {
+    pub index: usize,
+    pub reason: &'static str,
+}
+
+impl Display for InvalidGenerator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "generator {}: {}", self.index, self.reason)
+    }
+}
+
+impl std::error::Error for InvalidGenerator {}
+
+impl SceneFile {
+    /// Checks that every generator can be sampled, and that bodies orbit a body of the file.
+    pub fn validate(&self) -> Result<(), InvalidGenerator> {
+        // Written so that NaN fails too.
+        let positive = |value: f32| value > 0.0;
+        let non_negative = |value: f32| value >= 0.0;
+
+        for (index, generator) in self.generators.iter().enumerate() {
+            let fail = |reason| Err(InvalidGenerator { index, reason });
+            let orbiting = match *generator {
+                Generator::Ring {
+                    radius, orbiting, ..
+                } => {
+                    if !positive(radius) {
+                        return fail("the radius must be positive");
+                    }
+                    orbiting
+                }
+                Generator::Disk {
+                    inner_radius,
+                    outer_radius,
+                    orbiting,
+                    ..
+                } => {
+                    if !non_negative(inner_radius) || !positive(outer_radius) {
+                        return fail("the radii must be positive");
+                    }
+                    if inner_radius > outer_radius {
+                        return fail("the inner radius must not exceed the outer radius");
+                    }
+                    // A body at the centre has no direction to orbit in.
+                    if orbiting.is_some() && inner_radius == 0.0 {
+                        return fail("the inner radius must be positive around a primary");
+                    }
+                    orbiting
+                }
+                Generator::Cloud {
+                    radius,
+                    velocity_dispersion,
+                    ..
+                } => {
+                    if !non_negative(radius) || !non_negative(velocity_dispersion) {
+                        return fail("the radius and velocity dispersion must not be negative");
+                    }
+                    None
+                }
+            };
+            if orbiting.is_some_and(|primary| primary >= self.bodies.len()) {
+                return fail("the orbited body is not in the file");
+            }
+        }
+        Ok(())
+    }
+
+    /// Lists the bodies of the file followed by those of its generators.
+    pub fn bodies(&self, rng: &mut impl Rng) -> Vec<BodySpawn> {
+        let mut spawns: Vec<_> = self
+            .bodies
+            .iter()
+            .map(|body| body.spawn(body.name.clone(), vec3(body.position), vec3(body.velocity)))
+            .collect();
+
+        for generator in &self.generators {
+            let (center, orbiting, template) = match generator {
+                Generator::Ring {
+                    center,
+                    orbiting,
+                    body,
+                    ..
+                }
+                | Generator::Disk {
+                    center,
+                    orbiting,
+                    body,
+                    ..
+                } => (*center, *orbiting, body),
+                Generator::Cloud { center, body, .. } => (*center, None, body),
+            };
+            let primary = orbiting.and_then(|index| self.bodies.get(index));
+            let center = primary.map_or(vec3(center), |p| vec3(p.position));
+
+            let offsets: Vec<Vec3> = match *generator {
+                Generator::Ring { radius, count, .. } => (0..count)
+                    .map(|i| {
+                        let theta = TAU * i as f32 / count as f32;
+                        Vec3::new(theta.cos(), theta.sin(), 0.0) * radius
+                    })
+                    .collect(),
+                Generator::Disk {
+                    inner_radius,
+                    outer_radius,
+                    count,
+                    ..
+                } => {
+                    let (inner, outer) = (inner_radius.powi(2), outer_radius.powi(2));
+                    (0..count)
+                        .map(|_| {
+                            let radius = rng.gen_range(inner..=outer).sqrt();
+                            let theta = rng.gen_range(0.0..TAU);
+                            Vec3::new(theta.cos(), theta.sin(), 0.0) * radius
+                        })
+                        .collect()
+                }
+                Generator::Cloud { radius, count, .. } => (0..count)
+                    .map(|_| {
+                        let distance = radius * rng.gen_range(0.0_f32..=1.0).sqrt();
+                        let theta = rng.gen_range(0.0..TAU);
+                        Vec3::new(theta.cos(), theta.sin(), 0.0) * distance
+                    })
+                    .collect(),
+            };
+
+            for (i, offset) in offsets.into_iter().enumerate() {
+                let velocity = match (generator, primary) {
+                    (
+                        Generator::Cloud {
+                            velocity,
+                            velocity_dispersion,
+                            ..
+                        },
+                        _,
+                    ) => {
+                        let theta = rng.gen_range(0.0..TAU);
+                        let speed = velocity_dispersion * rng.gen_range(0.0_f32..=1.0);
+                        vec3(*velocity) + Vec3::new(theta.cos(), theta.sin(), 0.0) * speed
+                    }
+                    (_, Some(primary)) => {
+                        let mass = primary.mass
+                            + if template.massless {
+                                0.0
+                            } else {
+                                template.mass
+                            };
+                        let speed = (G * mass / offset.length()).sqrt();
+                        vec3(primary.velocity) + Vec3::Z.cross(offset.normalize()) * speed
+                    }
+                    (_, None) => Vec3::ZERO,
+                };
+
+                let name = template.name.as_ref().map(|name| format!("{name} {i}"));
+                spawns.push(template.spawn(name, center + offset, velocity));
+            }
+        }
+
+        spawns
+    }
+}
+
+#[derive(Default)]
+pub struct SceneFileLoader;
+
+impl AssetLoader for SceneFileLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), bevy::asset::Error>> {
+        Box::pin(async move {
+            let file: SceneFile = match load_context.path().extension() {
+                Some(extension) if extension == "json" => serde_json::from_slice(bytes)?,
+                _ => ron::de::from_bytes(bytes)?,
+            };
+            file.validate()?;
+            load_context.set_default_asset(LoadedAsset::new(file));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["sim.ron", "sim.json"]
+    }
+}
+
+#[derive(Clone)]
+pub struct FileScene {
+    file: Arc<SceneFile>,
+    seed: u64,
+}
+
+impl FileScene {
+    pub fn new(file: SceneFile) -> Self {
+        Self {
+            seed: file.seed,
+            file: Arc::new(file),
+        }
+    }
+}
+
+impl Display for FileScene {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.file.name)
+    }
+}
+
+impl SceneParams for FileScene {
+    fn show_ui(&mut self, ui: &mut Ui) {
+        ui.separator();
+        ui.label(format!(
+            "{} bodies and {} generators, loaded from a scene file.",
+            self.file.bodies.len(),
+            self.file.generators.len()
+        ));
+        ui.label("Saving the file reloads the scene.");
+        if !self.file.generators.is_empty() {
+            seed_picker(ui, &mut self.seed);
+        }
+    }
+
+    fn parameters(&self) -> Option<String> {
+        ron::to_string(&self.seed).ok()
+    }
+
+    fn set_parameters(&mut self, parameters: &str) -> ron::Result<()> {
+        self.seed = ron::from_str(parameters)?;
+        Ok(())
+    }
+
+    fn reset(&mut self) {
+        self.seed = self.file.seed;
+    }
+}
+
+impl SceneData for FileScene {
+    fn instance(&self, mut scene_commands: EntityCommands, asset_server: Res<AssetServer>) {
+        let bodies = self.file.bodies(&mut ChaCha8Rng::seed_from_u64(self.seed));
+
+        scene_commands.with_children(|child| {
+            for body in bodies {
+                let mut entity = child.spawn_bundle(BodyBundle::new(
+                    body.position,
+                    Velocity::from_linear(body.velocity),
+                    body.density,
+                    body.physics_mass,
+                    body.point_mass,
+                    body.color,
+                    &asset_server,
+                ));
+                if let Some(name) = body.name {
+                    entity.insert(Name::new(name));
+                }
+                if let Some(length) = body.trail {
+                    entity.insert(Trail::new(length));
+                }
+            }
+        });
+    }
+
+    fn spawnable(&self) -> Spawnable {
+        self.file.spawnable
+    }
+}
+
+/// Keeps the scene files of `assets/scenes` loaded.
+pub struct SceneFiles {
+    _handles: Vec<HandleUntyped>,
+}
+
+pub fn load_scene_files(mut commands: Commands, asset_server: Res<AssetServer>) {
+    if let Err(error) = asset_server.watch_for_changes() {
+        warn!("Scene files will not hot reload: {error:?}");
+    }
+
+    let handles = asset_server.load_folder("scenes").unwrap_or_else(|error| {
+        warn!("No scene files loaded: {error:?}");
+        Vec::new()
+    });
+    commands.insert_resource(SceneFiles { _handles: handles });
+}
+
+pub fn register_scene_files(
+    mut events: EventReader<AssetEvent<SceneFile>>,
+    files: Res<Assets<SceneFile>>,
+    asset_server: Res<AssetServer>,
+    mut scenes: ResMut<SceneCollection>,
+    mut loaded: ResMut<LoadedScene>,
+) {
+    for event in events.iter() {
+        let (handle, modified) = match event {
+            AssetEvent::Created { handle } => (handle, false),
+            AssetEvent::Modified { handle } => (handle, true),
+            AssetEvent::Removed { .. } => continue,
+        };
+        let (file, path) = match (files.get(handle), asset_server.get_handle_path(handle)) {
+            (Some(file), Some(path)) => (file, path),
+            _ => continue,
+        };
+        let info = SceneInfo::new(format!("file:{}", path.path().display()), &file.name)
+            .with_description(&file.description)
+            .with_category(file.category.as_deref().unwrap_or("Files"));
+        let scene = FileScene::new(file.clone());
+
+        if modified && loaded.id() == info.id {
+            loaded.load(&info.id, Box::new(scene.clone()));
+        }
+        scenes.insert(info, Box::new(scene));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::mock::StepRng;
+
+    use super::*;
+
+    #[test]
+    fn ring_bodies_orbit_their_primary() {
+        let file: SceneFile = ron::from_str(
+            r#"(
+                name: "Ring",
+                bodies: [(position: (100.0, 0.0), mass: 1000.0)],
+                generators: [
+                    Ring(orbiting: Some(0), radius: 50.0, count: 4, body: (massless: true)),
+                ],
+            )"#,
+        )
+        .unwrap();
+
+        let bodies = file.bodies(&mut StepRng::new(0, 1));
+
+        assert_eq!(bodies.len(), 5);
+        let speed = (G * 1000.0 / 50.0).sqrt();
+        for body in &bodies[1..] {
+            let offset = body.position - Vec3::new(100.0, 0.0, 0.0);
+            assert!((offset.length() - 50.0).abs() < 1E-3);
+            assert!((body.velocity.length() - speed).abs() < 1E-3);
+            assert!(offset.dot(body.velocity).abs() < 1E-2);
+        }
+    }
+
+    #[test]
+    fn degenerate_generators_are_rejected() {
+        let generator = |generator: &str| {
+            let file: SceneFile = ron::from_str(&format!(
+                "(name: \"Invalid\", bodies: [()], generators: [{generator}])"
+            ))
+            .unwrap();
+            file.validate().map_err(|error| error.reason)
+        };
+
+        assert!(generator("Disk(inner_radius: 10.0, outer_radius: 20.0, count: 5)").is_ok());
+        assert!(generator("Disk(inner_radius: 20.0, outer_radius: 10.0, count: 5)").is_err());
+        assert!(generator(
+            "Disk(orbiting: Some(0), inner_radius: 0.0, outer_radius: 10.0, count: 5)"
+        )
+        .is_err());
+        assert!(generator("Ring(radius: 0.0, count: 5)").is_err());
+        assert!(generator("Ring(orbiting: Some(1), radius: 10.0, count: 5)").is_err());
+        assert!(generator("Cloud(radius: -1.0, count: 5)").is_err());
+    }
+
+    #[test]
+    fn example_files_parse() {
+        let binary: SceneFile =
+            ron::from_str(include_str!("../../assets/scenes/binary_disk.sim.ron")).unwrap();
+        let cloud: SceneFile =
+            serde_json::from_str(include_str!("../../assets/scenes/cloud.sim.json")).unwrap();
+
+        assert!(binary.validate().is_ok() && cloud.validate().is_ok());
+        assert_eq!(binary.bodies(&mut ChaCha8Rng::seed_from_u64(0)).len(), 164);
+        assert_eq!(cloud.bodies(&mut ChaCha8Rng::seed_from_u64(0)).len(), 60);
+    }
+}
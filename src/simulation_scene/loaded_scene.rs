@@ -10,26 +10,34 @@ use bevy::{
 use crate::Spawnable;
 
 pub struct LoadedScene {
+    /// Id of the scene in the [`crate::SceneCollection`].
+    id: String,
     scene: SimulationScene,
     entity: Option<Entity>,
 }
 
 impl LoadedScene {
-    pub fn new<S>(scene: S) -> Self
+    pub fn new<S>(id: impl Into<String>, scene: S) -> Self
     where
         S: SceneData + Send + Sync + 'static,
     {
         Self {
+            id: id.into(),
             scene: Box::new(scene),
             entity: None,
         }
     }
 
-    pub fn load(&mut self, scene: SimulationScene) {
+    pub fn load(&mut self, id: impl Into<String>, scene: SimulationScene) {
+        self.id = id.into();
         self.scene = scene;
     }
 
-    pub fn loaded(&mut self) -> &SimulationScene {
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn loaded(&self) -> &SimulationScene {
         &self.scene
     }
 
@@ -6,7 +6,7 @@ use bevy::{
 };
 use bevy_egui::egui::Ui;
 
-use super::Spawnable;
+use super::{SceneParams, Spawnable};
 
 pub type SimulationScene = Box<dyn SceneData + Send + Sync>;
 
@@ -20,11 +20,9 @@ impl<T: 'static + SceneData + Send + Sync + Clone> SceneDataClone for T {
     }
 }
 
-pub trait SceneData: SceneDataClone + Display {
+pub trait SceneData: SceneDataClone + SceneParams + Display {
     fn instance(&self, scene_commands: EntityCommands, asset_server: Res<AssetServer>);
 
-    fn show_ui(&mut self, ui: &mut Ui);
-
     fn spawnable(&self) -> Spawnable;
 }
 
@@ -34,12 +32,6 @@ impl Clone for SimulationScene {
     }
 }
 
-impl PartialEq for SimulationScene {
-    fn eq(&self, other: &Self) -> bool {
-        self.to_string() == other.to_string()
-    }
-}
-
 #[derive(Clone, Default)]
 pub struct Empty;
 
@@ -49,11 +41,13 @@ impl Display for Empty {
     }
 }
 
+impl SceneParams for Empty {
+    fn show_ui(&mut self, _: &mut Ui) {}
+}
+
 impl SceneData for Empty {
     fn instance(&self, _: EntityCommands, _: Res<AssetServer>) {}
 
-    fn show_ui(&mut self, _: &mut Ui) {}
-
     fn spawnable(&self) -> Spawnable {
         Spawnable::Massive {
             min_mass: 1.0,
@@ -0,0 +1,70 @@
+use bevy_egui::egui::{DragValue, Ui};
+
+pub use scene_params_derive::SceneParams;
+
+/// Tunable parameters of a scene, usually implemented with `#[derive(SceneParams)]`.
+pub trait SceneParams {
+    fn show_ui(&mut self, ui: &mut Ui);
+
+    /// Parameters of the scene as RON, saved in snapshots.
+    fn parameters(&self) -> Option<String> {
+        None
+    }
+
+    fn set_parameters(&mut self, _parameters: &str) -> ron::Result<()> {
+        Ok(())
+    }
+
+    fn reset(&mut self) {}
+}
+
+/// Edits the seed of a random scene, with a button drawing a new one.
+pub fn seed_picker(ui: &mut Ui, seed: &mut u64) {
+    ui.horizontal(|ui| {
+        ui.add(DragValue::new(seed));
+        ui.label("Seed");
+        // Small enough to survive the round trip through the `f64` of the drag value.
+        if ui.button("Reroll").clicked() {
+            *seed = rand::random::<u32>().into();
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Clone, Debug, PartialEq, SceneParams)]
+    #[scene(name = "Test scene")]
+    struct TestScene {
+        #[param(default = 2.5, range = 0.0..=10.0, log)]
+        radius: f32,
+        #[param(default = 12)]
+        count: usize,
+        visible: bool,
+    }
+
+    #[test]
+    fn derived_parameters_round_trip() {
+        let mut scene = TestScene::default();
+        assert_eq!(scene.to_string(), "Test scene");
+        assert_eq!((scene.radius, scene.count, scene.visible), (2.5, 12, false));
+
+        let changed = TestScene {
+            radius: 7.0,
+            count: 3,
+            visible: true,
+        };
+        scene
+            .set_parameters(&changed.parameters().unwrap())
+            .unwrap();
+        assert_eq!(scene, changed);
+
+        // Fields missing from older parameters keep their default.
+        scene.set_parameters("(count: 5)").unwrap();
+        assert_eq!((scene.radius, scene.count), (2.5, 5));
+
+        scene.reset();
+        assert_eq!(scene, TestScene::default());
+    }
+}
@@ -1,4 +1,8 @@
-#[derive(Clone, Copy)]
+use serde::Deserialize;
+
+use crate::nbody::PointMass;
+
+#[derive(Clone, Copy, Deserialize)]
 pub enum Spawnable {
     Massive {
         min_mass: f32,
@@ -41,6 +45,17 @@ impl Spawnable {
         }
     }
 
+    /// Returns the density, physics mass and point mass of a body of this kind, massless if `mass` is `None`.
+    pub fn body(self, mass: Option<f32>) -> (f32, f32, PointMass) {
+        match (self, mass) {
+            (Spawnable::Massive { density, .. }, Some(mass)) => {
+                (density, mass, PointMass::HasGravity { mass })
+            }
+            (Spawnable::Massive { .. }, None) => (0.001, 0.01, PointMass::AffectedByGravity),
+            (Spawnable::Massless { density }, _) => (density, 0.01, PointMass::AffectedByGravity),
+        }
+    }
+
     pub fn is_massive(&self) -> bool {
         matches!(self, Spawnable::Massive { .. })
     }
@@ -1,4 +1,4 @@
-use crate::LoadedScene;
+use crate::{trails::TrailBuffers, LoadedScene};
 use bevy::{
     ecs::{
         change_detection::DetectChanges,
@@ -26,11 +26,13 @@ pub fn _scene_changed(scene: Res<LoadedScene>) -> ShouldRun {
 pub fn scene_cleanup_and_reload(
     mut commands: Commands,
     mut lines: ResMut<DebugLines>,
+    mut trails: ResMut<TrailBuffers>,
     mut scene: ResMut<LoadedScene>,
     asset_server: Res<AssetServer>,
 ) {
     if scene.is_changed() {
         *lines = DebugLines::default();
+        trails.clear();
 
         let entity_commands = if let Some(entity) = scene.get_entity() {
             let mut commands = commands.entity(entity);
@@ -50,23 +52,47 @@ pub fn show_ui(
     mut egui_ctx: ResMut<bevy_egui::EguiContext>,
     mut scenes: ResMut<SceneCollection>,
     mut scene: ResMut<LoadedScene>,
-    mut selected: Local<Option<usize>>,
+    mut selected: Local<Option<String>>,
 ) {
-    if let Some(selected) = selected.as_mut() {
-        egui::Window::new("Simulation").show(egui_ctx.ctx_mut(), |ui| {
-            ui.with_layout(egui::Layout::left_to_right(egui::Align::Min), |ui| {
-                egui::ComboBox::from_label("")
-                    .show_index(ui, selected, scenes.len(), |i| scenes[i].to_string());
+    let selected = selected.get_or_insert_with(|| scene.id().to_string());
+    if scenes.get(selected).is_none() {
+        match scenes.iter().next() {
+            Some(first) => *selected = first.info.id.clone(),
+            None => return,
+        }
+    }
+
+    egui::Window::new("Simulation").show(egui_ctx.ctx_mut(), |ui| {
+        ui.with_layout(egui::Layout::left_to_right(egui::Align::Min), |ui| {
+            egui::ComboBox::from_id_source("scene")
+                .selected_text(&scenes.get(selected).unwrap().info.name)
+                .show_ui(ui, |ui| {
+                    for category in scenes.categories() {
+                        ui.label(egui::RichText::new(category).strong());
+                        for registered in scenes.iter().filter(|s| s.info.category == category) {
+                            let info = &registered.info;
+                            let label = ui.selectable_value(selected, info.id.clone(), &info.name);
+                            if !info.description.is_empty() {
+                                label.on_hover_text(&info.description);
+                            }
+                        }
+                    }
+                });
 
-                if ui.button("New").clicked() {
-                    let selected_scene = scenes[*selected].clone();
-                    scene.load(selected_scene);
-                }
-            });
+            if ui.button("New").clicked() {
+                let selected_scene = scenes.get(selected).unwrap().scene.clone();
+                scene.load(selected.clone(), selected_scene);
+            }
 
-            scenes[*selected].show_ui(ui);
+            if ui.button("Reset").clicked() {
+                scenes.get_mut(selected).unwrap().scene.reset();
+            }
         });
-    } else {
-        *selected = scenes.iter().position(|s| s == scene.loaded());
-    }
+
+        let registered = scenes.get_mut(selected).unwrap();
+        if !registered.info.description.is_empty() {
+            ui.label(&registered.info.description);
+        }
+        registered.scene.show_ui(ui);
+    });
 }
@@ -1,45 +1,60 @@
-use std::{
-    f32::consts::{PI, TAU},
-    fmt::Display,
-};
+use std::f32::consts::{PI, TAU};
 
 use bevy::{
     ecs::system::{EntityCommands, Res},
     prelude::{AssetServer, BuildChildren, Color, Name, Vec3},
 };
-use bevy_egui::egui::{self, Slider, Ui};
 use heron::Velocity;
-use rand::{thread_rng, Rng};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
 
 use crate::{
-    nbody::PointMass, simulation_scene::Spawnable, trails::Trail, BodyBundle, SceneData, G,
+    nbody::PointMass,
+    simulation_scene::{seed_picker, SceneParams, Spawnable},
+    BodyBundle, SceneData, G,
 };
 
-#[derive(Clone)]
+#[derive(Clone, SceneParams)]
 pub struct Orbits {
+    #[param(default = 1E5, range = 1E3..=1E6, log, label = "Mass", section = "Central body:")]
     main_mass: f32,
+    #[param(default = 20.0, hidden)]
     main_density: f32,
+    #[param(default = 1000, range = 1..=5000, log, label = "Body count", section = "Orbiting bodies:")]
     bodies_count: usize,
+    #[param(default = 0.1, hidden)]
     bodies_density: f32,
+    #[param(
+        default = 1000.0,
+        range = self.min_spawnable_position()..=10000.0,
+        log,
+        integer,
+        label = "Position range"
+    )]
     bodies_max_pos: f32,
+    #[param(default = 1.0, hidden)]
     bodies_min_mass: f32,
+    #[param(
+        default = 10.0,
+        range = self.bodies_min_mass..=self.spawnable().max_mass().unwrap(),
+        label = "Mass range",
+        enabled = self.bodies_with_mass
+    )]
     bodies_max_mass: f32,
+    #[param(default = true, label = "With mass")]
     bodies_with_mass: bool,
+    #[param(default = 0, widget = seed_picker)]
+    seed: u64,
 }
 
-impl Default for Orbits {
-    fn default() -> Self {
-        Self {
-            main_mass: 1E5,
-            main_density: 20.0,
-            bodies_count: 1000,
-            bodies_density: 0.1,
-            bodies_max_pos: 1000.0,
-            bodies_min_mass: 1.0,
-            bodies_max_mass: 10.0,
-            bodies_with_mass: true,
-        }
-    }
+/// An orbiting body, before it is spawned.
+struct Particle {
+    position: Vec3,
+    velocity: Vec3,
+    density: f32,
+    mass: f32,
+    point_mass: PointMass,
+    color: Color,
 }
 
 impl Orbits {
@@ -50,35 +65,16 @@ impl Orbits {
     fn min_spawnable_position(&self) -> f32 {
         ((self.bodies_count as f32).sqrt() * self.bodies_max_mass).max(self.main_radius() * 4.0)
     }
-}
 
-impl Display for Orbits {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "Orbits")
-    }
-}
+    /// The orbiting bodies, the same for the same parameters and seed.
+    fn particles(&self) -> Vec<Particle> {
+        let mut rng = ChaCha8Rng::seed_from_u64(self.seed);
 
-impl SceneData for Orbits {
-    fn instance(&self, mut scene_commands: EntityCommands, asset_server: Res<AssetServer>) {
-        let mut rng = thread_rng();
+        let min_radius = 2.0 * self.main_radius();
+        let min_p_sqrt = min_radius * min_radius / (self.bodies_max_pos * self.bodies_max_pos);
 
-        scene_commands.with_children(|child| {
-            child.spawn_bundle(BodyBundle::new(
-                Vec3::ZERO,
-                Velocity::from_linear(Vec3::ZERO),
-                self.main_density,
-                self.main_mass,
-                PointMass::HasGravity {
-                    mass: self.main_mass,
-                },
-                Color::WHITE,
-                &asset_server,
-            ));
-
-            let min_radius = 2.0 * self.main_radius();
-            let min_p_sqrt = min_radius * min_radius / (self.bodies_max_pos * self.bodies_max_pos);
-
-            for i in 0..self.bodies_count {
+        (0..self.bodies_count)
+            .map(|_| {
                 let radius = self.bodies_max_pos * rng.gen_range(min_p_sqrt..=1.0).sqrt();
                 let theta = rng.gen_range(0.0..=TAU);
 
@@ -105,14 +101,45 @@ impl SceneData for Orbits {
                 let mut random_color = || rng.gen_range(0.0..=1.0_f32);
                 let (r, g, b) = (random_color(), random_color(), random_color());
 
+                Particle {
+                    position,
+                    velocity: velvec,
+                    density,
+                    mass: physics_mass,
+                    point_mass,
+                    color: Color::rgb(r, g, b),
+                }
+            })
+            .collect()
+    }
+}
+
+impl SceneData for Orbits {
+    fn instance(&self, mut scene_commands: EntityCommands, asset_server: Res<AssetServer>) {
+        let particles = self.particles();
+
+        scene_commands.with_children(|child| {
+            child.spawn_bundle(BodyBundle::new(
+                Vec3::ZERO,
+                Velocity::from_linear(Vec3::ZERO),
+                self.main_density,
+                self.main_mass,
+                PointMass::HasGravity {
+                    mass: self.main_mass,
+                },
+                Color::WHITE,
+                &asset_server,
+            ));
+
+            for (i, particle) in particles.into_iter().enumerate() {
                 child
                     .spawn_bundle(BodyBundle::new(
-                        position,
-                        Velocity::from_linear(velvec),
-                        density,
-                        physics_mass,
-                        point_mass,
-                        Color::rgb(r, g, b),
+                        particle.position,
+                        Velocity::from_linear(particle.velocity),
+                        particle.density,
+                        particle.mass,
+                        particle.point_mass,
+                        particle.color,
                         &asset_server,
                     ))
                     .insert(Name::new(format!("Particle {}", i)));
@@ -120,49 +147,6 @@ impl SceneData for Orbits {
         });
     }
 
-    fn show_ui(&mut self, ui: &mut Ui) {
-        ui.separator();
-
-        ui.label("Central body:");
-        {
-            ui.add(
-                Slider::new(&mut self.main_mass, 1E3..=1E6)
-                    .logarithmic(true)
-                    .text("Mass"),
-            );
-        }
-
-        ui.separator();
-
-        ui.label("Orbiting bodies:");
-        {
-            ui.add(
-                Slider::new(&mut self.bodies_count, 1..=5000)
-                    .text(" Body count")
-                    .logarithmic(true),
-            );
-
-            let min_pos = self.min_spawnable_position();
-            ui.add(
-                Slider::new(&mut self.bodies_max_pos, min_pos..=10000.0)
-                    .text(" Position range")
-                    .logarithmic(true)
-                    .integer(),
-            );
-
-            ui.with_layout(egui::Layout::left_to_right(egui::Align::Min), |ui| {
-                let (min_mass, max_mass) =
-                    (self.bodies_min_mass, self.spawnable().max_mass().unwrap());
-                ui.add_enabled(
-                    self.bodies_with_mass,
-                    Slider::new(&mut self.bodies_max_mass, min_mass..=max_mass),
-                );
-
-                ui.toggle_value(&mut self.bodies_with_mass, "Mass range");
-            });
-        }
-    }
-
     fn spawnable(&self) -> Spawnable {
         Spawnable::Massive {
             min_mass: 1.0,
@@ -172,266 +156,44 @@ impl SceneData for Orbits {
     }
 }
 
-#[derive(Clone)]
-pub struct Figure8 {
-    radius: f32,
-    mass: f32,
-}
-
-impl Default for Figure8 {
-    fn default() -> Self {
-        Self {
-            radius: 30.0,
-            mass: 1E5,
-        }
-    }
-}
-
-impl Display for Figure8 {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "Figure8")
-    }
-}
-
-impl SceneData for Figure8 {
-    fn instance(&self, mut scene_commands: EntityCommands, asset_server: Res<AssetServer>) {
-        let mass = self.mass;
-        let density = 0.5 * mass / (self.radius.powi(2) * PI);
-        let distance = (G * mass).cbrt();
-
-        let pos1 = Vec3::new(-0.970_004_4, 0.243_087_53, 0.0) * distance;
-        let pos2 = Vec3::ZERO;
-
-        let vel1 = Vec3::new(0.466_203_7, 0.432_365_73, 0.0) * distance;
-        let vel2 = -2.0 * vel1;
-
-        scene_commands.with_children(|child| {
-            child
-                .spawn_bundle(BodyBundle::new(
-                    pos1,
-                    Velocity::from_linear(vel1),
-                    density,
-                    mass,
-                    PointMass::HasGravity { mass },
-                    Color::WHITE,
-                    &asset_server,
-                ))
-                .insert(Trail::new(15.0, 1));
-
-            child
-                .spawn_bundle(BodyBundle::new(
-                    -pos1,
-                    Velocity::from_linear(vel1),
-                    density,
-                    mass,
-                    PointMass::HasGravity { mass },
-                    Color::WHITE,
-                    &asset_server,
-                ))
-                .insert(Trail::new(15.0, 1));
-
-            child
-                .spawn_bundle(BodyBundle::new(
-                    pos2,
-                    Velocity::from_linear(vel2),
-                    density,
-                    mass,
-                    PointMass::HasGravity { mass },
-                    Color::WHITE,
-                    &asset_server,
-                ))
-                .insert(Trail::new(15.0, 1));
-        });
-    }
-
-    fn show_ui(&mut self, ui: &mut Ui) {
-        ui.add(
-            Slider::new(&mut self.radius, 5.0..=100.0)
-                .text("Radius")
-                .logarithmic(true)
-                .integer(),
-        );
-    }
-
-    fn spawnable(&self) -> Spawnable {
-        Spawnable::Massless {
-            density: 3E-7 * self.mass / (self.radius * self.radius * PI),
-        }
-    }
-}
-
-#[derive(Clone)]
-pub struct TernaryOrbit {
-    radius: f32,
-    mass: f32,
-}
-
-impl Default for TernaryOrbit {
-    fn default() -> Self {
-        Self {
-            radius: 20.0,
-            mass: 1E5,
-        }
-    }
-}
-
-impl Display for TernaryOrbit {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "TernaryOrbit")
-    }
-}
-
-impl SceneData for TernaryOrbit {
-    fn instance(&self, mut scene_commands: EntityCommands, asset_server: Res<AssetServer>) {
-        let mass: f32 = self.mass;
-        let density = mass / (self.radius.powi(2) * PI);
-        let distance = (G * mass).cbrt();
-
-        let pos1 = Vec3::new(1.0, 0.0, 0.0) * distance;
-        let pos2 = Vec3::new(-0.5, 3.0_f32.sqrt() / 2.0, 0.0) * distance;
-        let pos3 = Vec3::new(-0.5, -(3.0_f32.sqrt()) / 2.0, 0.0) * distance;
-
-        let vel1 = Vec3::new(0.0, 1.0, 0.0) * distance * 0.5;
-        let vel2 = Vec3::new(-(3.0_f32.sqrt()) / 2.0, -0.5, 0.0) * distance * 0.5;
-        let vel3 = Vec3::new(3.0_f32.sqrt() / 2.0, -0.5, 0.0) * distance * 0.5;
-
-        scene_commands.with_children(|child| {
-            child
-                .spawn_bundle(BodyBundle::new(
-                    pos1,
-                    Velocity::from_linear(vel1),
-                    density,
-                    mass,
-                    PointMass::HasGravity { mass },
-                    Color::WHITE,
-                    &asset_server,
-                ))
-                .insert(Trail::new(15.0, 1));
-
-            child
-                .spawn_bundle(BodyBundle::new(
-                    pos2,
-                    Velocity::from_linear(vel2),
-                    density,
-                    mass,
-                    PointMass::HasGravity { mass },
-                    Color::WHITE,
-                    &asset_server,
-                ))
-                .insert(Trail::new(15.0, 1));
-
-            child
-                .spawn_bundle(BodyBundle::new(
-                    pos3,
-                    Velocity::from_linear(vel3),
-                    density,
-                    mass,
-                    PointMass::HasGravity { mass },
-                    Color::WHITE,
-                    &asset_server,
-                ))
-                .insert(Trail::new(15.0, 1));
-        });
-    }
-
-    fn show_ui(&mut self, ui: &mut Ui) {
-        ui.add(
-            Slider::new(&mut self.radius, 5.0..=100.0)
-                .text("Radius")
-                .logarithmic(true)
-                .integer(),
-        );
-    }
-
-    fn spawnable(&self) -> Spawnable {
-        Spawnable::Massless { density: 1E-4 }
-    }
-}
-
-#[derive(Clone)]
-pub struct DoubleOval {
-    radius: f32,
-    mass: f32,
-}
-
-impl Default for DoubleOval {
-    fn default() -> Self {
-        Self {
-            radius: 20.0,
-            mass: 1E5,
-        }
-    }
-}
-
-impl Display for DoubleOval {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "DoubleOval")
-    }
-}
-
-impl SceneData for DoubleOval {
-    fn instance(&self, mut scene_commands: EntityCommands, asset_server: Res<AssetServer>) {
-        let mass: f32 = self.mass;
-        let density = mass / (self.radius.powi(2) * PI);
-        let distance = (G * mass).cbrt();
-
-        let pos1 = Vec3::new(0.486_657_68, 0.755_041_9, 0.0) * distance;
-        let pos2 = Vec3::new(-0.681_738, 0.293_660_22, 0.0) * distance;
-        let pos3 = Vec3::new(-0.022_596_328, -0.612_645_6, 0.0) * distance;
-
-        let vel1 = Vec3::new(-0.182_709_86, 0.363_013_3, 0.0) * distance;
-        let vel2 = Vec3::new(-0.579_074_9, -0.748_157_5, 0.0) * distance;
-        let vel3 = Vec3::new(0.761_784_8, 0.385_144_2, 0.0) * distance;
-
-        scene_commands.with_children(|child| {
-            child
-                .spawn_bundle(BodyBundle::new(
-                    pos1,
-                    Velocity::from_linear(vel1),
-                    density,
-                    mass,
-                    PointMass::HasGravity { mass },
-                    Color::WHITE,
-                    &asset_server,
-                ))
-                .insert(Trail::new(15.0, 1));
-
-            child
-                .spawn_bundle(BodyBundle::new(
-                    pos2,
-                    Velocity::from_linear(vel2),
-                    density,
-                    mass,
-                    PointMass::HasGravity { mass },
-                    Color::WHITE,
-                    &asset_server,
-                ))
-                .insert(Trail::new(15.0, 1));
-
-            child
-                .spawn_bundle(BodyBundle::new(
-                    pos3,
-                    Velocity::from_linear(vel3),
-                    density,
-                    mass,
-                    PointMass::HasGravity { mass },
-                    Color::WHITE,
-                    &asset_server,
-                ))
-                .insert(Trail::new(15.0, 1));
-        });
-    }
-
-    fn show_ui(&mut self, ui: &mut Ui) {
-        ui.add(
-            Slider::new(&mut self.radius, 5.0..=50.0)
-                .text("Radius")
-                .logarithmic(true)
-                .integer(),
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bits(orbits: &Orbits) -> Vec<[u32; 8]> {
+        orbits
+            .particles()
+            .iter()
+            .map(|p| {
+                let [r, g, b, _] = p.color.as_rgba_f32();
+                [
+                    p.position.x.to_bits(),
+                    p.position.y.to_bits(),
+                    p.velocity.x.to_bits(),
+                    p.velocity.y.to_bits(),
+                    p.mass.to_bits(),
+                    r.to_bits(),
+                    g.to_bits(),
+                    b.to_bits(),
+                ]
+            })
+            .collect()
+    }
+
+    #[test]
+    fn seeded_orbits_are_reproducible() {
+        let orbits = Orbits {
+            seed: 42,
+            ..Orbits::default()
+        };
+
+        assert_eq!(bits(&orbits), bits(&orbits.clone()));
+        assert_ne!(
+            bits(&orbits),
+            bits(&Orbits {
+                seed: 43,
+                ..Orbits::default()
+            })
         );
     }
-
-    fn spawnable(&self) -> Spawnable {
-        Spawnable::Massless { density: 1E-4 }
-    }
 }
@@ -0,0 +1,281 @@
+use std::fmt::Display;
+
+use bevy::prelude::{Color, Vec3};
+use ron::ser::PrettyConfig;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    nbody::PointMass,
+    trails::{TrailColor, TrailSample},
+};
+
+/// Version written to new snapshots. Version 1 files are migrated, files with another version are
+/// rejected.
+pub const VERSION: u32 = 2;
+
+/// Ids of the scenes that version 1 snapshots named by their display name, with the solution of the
+/// scenes that became choreographies. The ternary orbit, an equilateral triangle, has none.
+const V1_SCENES: [(&str, &str, Option<&str>); 5] = [
+    ("Empty", "empty", None),
+    ("Orbits", "orbits", None),
+    ("Figure8", "choreography", Some("Figure-eight")),
+    ("TernaryOrbit", "choreography", None),
+    ("DoubleOval", "choreography", Some("Double oval")),
+];
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct SnapshotFile {
+    pub version: u32,
+    /// Simulation time in seconds.
+    pub time: f64,
+    pub scene: SceneState,
+    pub bodies: Vec<BodyState>,
+    /// What the migration of a version 1 snapshot left behind, to be reported on load.
+    #[serde(skip)]
+    pub dropped: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct SceneState {
+    /// Id of the scene in the scene collection. Version 1 snapshots stored its name instead.
+    #[serde(alias = "name")]
+    pub id: String,
+    /// RON of the scene's parameters, if it has any.
+    #[serde(default)]
+    pub parameters: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BodyState {
+    #[serde(default)]
+    pub name: Option<String>,
+    pub position: Vec3,
+    pub velocity: Vec3,
+    pub point_mass: PointMass,
+    /// Mass given to the physics engine, which sets the size of the body along with its density.
+    pub mass: f32,
+    pub density: f32,
+    pub color: Color,
+    #[serde(default)]
+    pub trail: Option<TrailState>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TrailState {
+    pub length: f32,
+    pub max_angle: f32,
+    pub max_distance: f32,
+    pub max_points: usize,
+    pub width: f32,
+    pub taper: f32,
+    pub color: TrailColor,
+    pub anchor: AnchorState,
+    /// Samples relative to the origin of the anchor, empty if the history was not saved.
+    #[serde(default)]
+    pub history: Vec<TrailSample>,
+}
+
+/// A [`crate::trails::TrailAnchor`], with bodies referred to by their index in the snapshot.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AnchorState {
+    View,
+    World,
+    Body(usize),
+    Barycentre,
+}
+
+#[derive(Debug)]
+pub enum SnapshotError {
+    Format(ron::Error),
+    Version(u32),
+}
+
+impl Display for SnapshotError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SnapshotError::Format(error) => write!(f, "{error}"),
+            SnapshotError::Version(version) => write!(
+                f,
+                "snapshot version {version} is not supported, expected {VERSION}"
+            ),
+        }
+    }
+}
+
+impl From<ron::Error> for SnapshotError {
+    fn from(error: ron::Error) -> Self {
+        SnapshotError::Format(error)
+    }
+}
+
+impl SceneState {
+    /// Replaces the name of a built-in scene by its id. Scene files keep their name, which is looked
+    /// up among the registered scenes on load.
+    ///
+    /// The parameters of the old choreographies have no equivalent in the choreography scene, so they
+    /// are dropped and described in the returned message.
+    fn migrate_v1(&mut self) -> Option<String> {
+        let &(name, id, solution) = V1_SCENES.iter().find(|(name, ..)| *name == self.id)?;
+        self.id = id.to_string();
+        if id != "choreography" {
+            return None;
+        }
+
+        let parameters = std::mem::replace(
+            &mut self.parameters,
+            solution.map(|solution| format!("(solution: {solution:?})")),
+        );
+        parameters
+            .filter(|parameters| !matches!(parameters.trim(), "" | "()"))
+            .map(|parameters| format!("the {name} parameters {parameters} were dropped"))
+    }
+}
+
+impl SnapshotFile {
+    pub fn new(time: f64, scene: SceneState, bodies: Vec<BodyState>) -> Self {
+        Self {
+            version: VERSION,
+            time,
+            scene,
+            bodies,
+            dropped: None,
+        }
+    }
+
+    pub fn to_ron(&self) -> Result<String, SnapshotError> {
+        Ok(ron::ser::to_string_pretty(self, PrettyConfig::default())?)
+    }
+
+    /// Parses a snapshot, checking its version before the rest of the file.
+    pub fn from_ron(text: &str) -> Result<Self, SnapshotError> {
+        #[derive(Deserialize)]
+        struct Header {
+            version: u32,
+        }
+
+        let Header { version } = ron::from_str(text)?;
+        if version != 1 && version != VERSION {
+            return Err(SnapshotError::Version(version));
+        }
+        let mut snapshot: Self = ron::from_str(text)?;
+        if version == 1 {
+            snapshot.dropped = snapshot.scene.migrate_v1();
+            snapshot.version = VERSION;
+        }
+        Ok(snapshot)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trails::Gradient;
+
+    fn snapshot() -> SnapshotFile {
+        let trail = TrailState {
+            length: 20.0,
+            max_angle: 0.05,
+            max_distance: 20.0,
+            max_points: 2048,
+            width: 2.5,
+            taper: 0.2,
+            color: TrailColor::Speed(Gradient::default()),
+            anchor: AnchorState::Body(0),
+            history: vec![
+                TrailSample {
+                    time: 1.25,
+                    position: Vec3::new(-3.5, 7.0, 0.0),
+                    color: Color::rgba(0.1, 0.2, 0.3, 0.4),
+                },
+                TrailSample {
+                    time: 1.0 / 3.0,
+                    position: Vec3::new(1E-7, 123456.79, 0.0),
+                    color: Color::WHITE,
+                },
+            ],
+        };
+
+        SnapshotFile::new(
+            12.345678901234,
+            SceneState {
+                id: "orbits".to_string(),
+                parameters: Some("(mass: 100000.0)".to_string()),
+            },
+            vec![
+                BodyState {
+                    name: Some("Sun".to_string()),
+                    position: Vec3::ZERO,
+                    velocity: Vec3::new(0.1, -0.2, 0.0),
+                    point_mass: PointMass::HasGravity { mass: 1E5 },
+                    mass: 1E5,
+                    density: 20.0,
+                    color: Color::WHITE,
+                    trail: None,
+                },
+                BodyState {
+                    name: None,
+                    position: Vec3::new(300.0, 0.1, 0.0),
+                    velocity: Vec3::new(0.0, 1.0 / 7.0, 0.0),
+                    point_mass: PointMass::AffectedByGravity,
+                    mass: 0.01,
+                    density: 0.001,
+                    color: Color::ORANGE,
+                    trail: Some(trail),
+                },
+            ],
+        )
+    }
+
+    #[test]
+    fn snapshots_round_trip() {
+        let snapshot = snapshot();
+        let text = snapshot.to_ron().unwrap();
+
+        assert_eq!(SnapshotFile::from_ron(&text).unwrap(), snapshot);
+    }
+
+    #[test]
+    fn version_1_scenes_are_migrated() {
+        let migrate = |name: &str| {
+            let text = format!(
+                "(version: 1, time: 0.0, scene: (name: {name:?}, parameters: Some(\"(radius: 30.0)\")), bodies: [])"
+            );
+            let snapshot = SnapshotFile::from_ron(&text).unwrap();
+            (
+                snapshot.scene.id,
+                snapshot.scene.parameters,
+                snapshot.dropped,
+            )
+        };
+
+        assert_eq!(
+            migrate("Orbits"),
+            (
+                "orbits".to_string(),
+                Some("(radius: 30.0)".to_string()),
+                None
+            )
+        );
+        assert_eq!(
+            migrate("Figure8"),
+            (
+                "choreography".to_string(),
+                Some("(solution: \"Figure-eight\")".to_string()),
+                Some("the Figure8 parameters (radius: 30.0) were dropped".to_string())
+            )
+        );
+        assert_eq!(migrate("My file").0, "My file");
+    }
+
+    #[test]
+    fn other_versions_are_rejected() {
+        let mut snapshot = snapshot();
+        snapshot.version = VERSION + 1;
+        let text = snapshot.to_ron().unwrap();
+
+        assert!(matches!(
+            SnapshotFile::from_ron(&text),
+            Err(SnapshotError::Version(v)) if v == VERSION + 1
+        ));
+    }
+}
@@ -0,0 +1,384 @@
+mod format;
+
+use std::{fmt::Display, sync::Arc};
+
+use bevy::{ecs::system::EntityCommands, prelude::*, utils::HashMap};
+use bevy_egui::{
+    egui::{self, Ui, Window},
+    EguiContext,
+};
+use heron::{CollisionShape, PhysicMaterial, Velocity};
+
+use self::format::{AnchorState, BodyState, SceneState, SnapshotError, SnapshotFile, TrailState};
+use crate::{
+    nbody::{PointMass, SimulationTime},
+    simulation_scene::{
+        Empty, LoadedScene, SceneCollection, SceneData, SceneParams, SimulationScene, Spawnable,
+    },
+    trails::{Trail, TrailAnchor, TrailBuffer, TrailBuffers, TrailSample},
+    BodyBundle,
+};
+
+pub struct SnapshotPlugin;
+
+impl Plugin for SnapshotPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SnapshotSettings>()
+            .add_system(snapshot_window)
+            .add_system(restore_trails);
+    }
+}
+
+pub struct SnapshotSettings {
+    pub path: String,
+    /// Whether saved snapshots include the recorded trail samples.
+    pub history: bool,
+    /// Outcome of the last save or load, shown in the window.
+    status: Option<String>,
+}
+
+impl Default for SnapshotSettings {
+    fn default() -> Self {
+        Self {
+            path: "snapshot.ron".to_string(),
+            history: true,
+            status: None,
+        }
+    }
+}
+
+type SnapshotBodies<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static Transform,
+        &'static Velocity,
+        &'static PointMass,
+        &'static PhysicMaterial,
+        &'static CollisionShape,
+        &'static Sprite,
+        Option<&'static Trail>,
+        Option<&'static Name>,
+    ),
+>;
+
+#[allow(clippy::too_many_arguments)]
+fn snapshot_window(
+    mut egui_ctx: ResMut<EguiContext>,
+    mut settings: ResMut<SnapshotSettings>,
+    mut scene: ResMut<LoadedScene>,
+    time: Res<SimulationTime>,
+    scenes: Res<SceneCollection>,
+    buffers: Res<TrailBuffers>,
+    children: Query<&Children>,
+    bodies: SnapshotBodies,
+) {
+    let (mut save, mut load) = (false, false);
+    Window::new("Snapshot").show(egui_ctx.ctx_mut(), |ui| {
+        ui.text_edit_singleline(&mut settings.path);
+        ui.checkbox(&mut settings.history, "Save trail history");
+        ui.with_layout(egui::Layout::left_to_right(egui::Align::Min), |ui| {
+            save = ui.button("Save snapshot").clicked();
+            load = ui.button("Load snapshot").clicked();
+        });
+        if let Some(status) = &settings.status {
+            ui.label(status);
+        }
+    });
+
+    if save {
+        let entities = scene
+            .get_entity()
+            .and_then(|entity| children.get(entity).ok())
+            .map_or(&[][..], |children| &children[..]);
+        let snapshot = capture(
+            entities,
+            &bodies,
+            &buffers,
+            settings.history,
+            time.0,
+            &scene,
+        );
+
+        settings.status = Some(
+            match snapshot
+                .to_ron()
+                .map_err(|error| error.to_string())
+                .and_then(|text| {
+                    std::fs::write(&settings.path, text).map_err(|error| error.to_string())
+                }) {
+                Ok(()) => format!("Saved {} bodies", snapshot.bodies.len()),
+                Err(error) => format!("Save failed: {error}"),
+            },
+        );
+    }
+
+    if load {
+        let snapshot = std::fs::read_to_string(&settings.path)
+            .map_err(|error| error.to_string())
+            .and_then(|text| SnapshotFile::from_ron(&text).map_err(|error| error.to_string()));
+
+        settings.status = Some(match snapshot {
+            Ok(mut snapshot) => {
+                // Version 1 snapshots of scene files name the scene rather than giving its id.
+                if scenes.get(&snapshot.scene.id).is_none() {
+                    if let Some(registered) =
+                        scenes.iter().find(|s| s.info.name == snapshot.scene.id)
+                    {
+                        snapshot.scene.id = registered.info.id.clone();
+                    }
+                }
+                let mut status = format!("Loaded {} bodies", snapshot.bodies.len());
+                if let Some(dropped) = &snapshot.dropped {
+                    status = format!("{status}, but {dropped}");
+                }
+                let id = snapshot.scene.id.clone();
+                match RestoredScene::new(snapshot, &scenes) {
+                    Ok(restored) => {
+                        scene.load(id, Box::new(restored));
+                        status
+                    }
+                    Err(error) => format!("Load failed: {error}"),
+                }
+            }
+            Err(error) => format!("Load failed: {error}"),
+        });
+    }
+}
+
+fn capture(
+    entities: &[Entity],
+    bodies: &SnapshotBodies,
+    buffers: &TrailBuffers,
+    history: bool,
+    time: f64,
+    scene: &LoadedScene,
+) -> SnapshotFile {
+    let entities: Vec<_> = entities
+        .iter()
+        .copied()
+        .filter(|&entity| bodies.contains(entity))
+        .collect();
+    let indices: HashMap<_, _> = entities.iter().enumerate().map(|(i, &e)| (e, i)).collect();
+
+    let bodies = entities
+        .iter()
+        .map(|&entity| {
+            let (transform, velocity, point_mass, material, shape, sprite, trail, name) =
+                bodies.get(entity).unwrap();
+            let radius = match shape {
+                CollisionShape::Sphere { radius } => *radius,
+                _ => 1.0,
+            };
+
+            let trail = trail.map(|trail| {
+                let anchor = match trail.anchor {
+                    TrailAnchor::View => AnchorState::View,
+                    TrailAnchor::World => AnchorState::World,
+                    TrailAnchor::Barycentre => AnchorState::Barycentre,
+                    TrailAnchor::Body(body) => indices
+                        .get(&body)
+                        .map_or(AnchorState::World, |&i| AnchorState::Body(i)),
+                };
+                let history = buffers
+                    .get(&entity)
+                    .filter(|buffer| history && buffer.anchor() == trail.anchor)
+                    .map_or_else(Vec::new, |buffer| buffer.samples().copied().collect());
+
+                TrailState {
+                    length: trail.length,
+                    max_angle: trail.max_angle,
+                    max_distance: trail.max_distance,
+                    max_points: trail.max_points,
+                    width: trail.width,
+                    taper: trail.taper,
+                    color: trail.color,
+                    anchor,
+                    history,
+                }
+            });
+
+            BodyState {
+                name: name.map(|name| name.to_string()),
+                position: transform.translation,
+                velocity: velocity.linear,
+                point_mass: *point_mass,
+                mass: material.density * std::f32::consts::PI * radius * radius,
+                density: material.density,
+                color: sprite.color,
+                trail,
+            }
+        })
+        .collect();
+
+    SnapshotFile::new(
+        time,
+        SceneState {
+            id: scene.id().to_string(),
+            parameters: scene.loaded().parameters(),
+        },
+        bodies,
+    )
+}
+
+/// Trail samples of a restored body, moved into its [`TrailBuffer`] once it is spawned.
+#[derive(Component)]
+struct TrailHistory(Vec<TrailSample>);
+
+fn restore_trails(
+    mut commands: Commands,
+    mut buffers: ResMut<TrailBuffers>,
+    query: Query<(Entity, &Trail, &TrailHistory)>,
+) {
+    for (entity, trail, history) in &query {
+        buffers.insert(
+            entity,
+            TrailBuffer::from_samples(history.0.iter().copied(), trail.anchor),
+        );
+        commands.entity(entity).remove::<TrailHistory>();
+    }
+}
+
+/// The scene a snapshot was taken in, spawning the bodies of the snapshot in place of its own and
+/// winding the clock back to when it was taken.
+#[derive(Clone)]
+struct RestoredScene {
+    scene: SimulationScene,
+    time: f64,
+    bodies: Arc<Vec<BodyState>>,
+}
+
+impl RestoredScene {
+    fn new(snapshot: SnapshotFile, scenes: &SceneCollection) -> Result<Self, SnapshotError> {
+        let mut scene = scenes
+            .get(&snapshot.scene.id)
+            .map_or_else(|| Box::new(Empty) as SimulationScene, |s| s.scene.clone());
+        if let Some(parameters) = &snapshot.scene.parameters {
+            scene.set_parameters(parameters)?;
+        }
+
+        Ok(Self {
+            scene,
+            time: snapshot.time,
+            bodies: Arc::new(snapshot.bodies),
+        })
+    }
+}
+
+impl Display for RestoredScene {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.scene.fmt(f)
+    }
+}
+
+impl SceneParams for RestoredScene {
+    fn show_ui(&mut self, ui: &mut Ui) {
+        self.scene.show_ui(ui);
+    }
+
+    fn parameters(&self) -> Option<String> {
+        self.scene.parameters()
+    }
+
+    fn set_parameters(&mut self, parameters: &str) -> ron::Result<()> {
+        self.scene.set_parameters(parameters)
+    }
+
+    fn reset(&mut self) {
+        self.scene.reset();
+    }
+}
+
+impl SceneData for RestoredScene {
+    fn instance(&self, mut scene_commands: EntityCommands, asset_server: Res<AssetServer>) {
+        let mut entities = Vec::with_capacity(self.bodies.len());
+        scene_commands.with_children(|child| {
+            for body in self.bodies.iter() {
+                let mut entity = child.spawn_bundle(BodyBundle::new(
+                    body.position,
+                    Velocity::from_linear(body.velocity),
+                    body.density,
+                    body.mass,
+                    body.point_mass,
+                    body.color,
+                    &asset_server,
+                ));
+                if let Some(name) = &body.name {
+                    entity.insert(Name::new(name.clone()));
+                }
+                entities.push(entity.id());
+            }
+        });
+
+        // Trails go in once every body has an entity, as they can be anchored to any of them.
+        let commands = scene_commands.commands();
+        commands.insert_resource(SimulationTime(self.time));
+        for (body, &entity) in self.bodies.iter().zip(&entities) {
+            if let Some(trail) = &body.trail {
+                let anchor = match trail.anchor {
+                    AnchorState::View => TrailAnchor::View,
+                    AnchorState::World => TrailAnchor::World,
+                    AnchorState::Barycentre => TrailAnchor::Barycentre,
+                    AnchorState::Body(i) => entities
+                        .get(i)
+                        .map_or(TrailAnchor::World, |&e| TrailAnchor::Body(e)),
+                };
+                commands.entity(entity).insert_bundle((
+                    Trail {
+                        length: trail.length,
+                        max_angle: trail.max_angle,
+                        max_distance: trail.max_distance,
+                        max_points: trail.max_points,
+                        width: trail.width,
+                        taper: trail.taper,
+                        color: trail.color,
+                        anchor,
+                    },
+                    TrailHistory(trail.history.clone()),
+                ));
+            }
+        }
+    }
+
+    fn spawnable(&self) -> Spawnable {
+        self.scene.spawnable()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::asset::AssetPlugin;
+    use bevy_prototype_debug_lines::DebugLines;
+    use heron::rapier_plugin::rapier2d::prelude::IntegrationParameters;
+
+    use super::*;
+    use crate::{nbody::advance_time, simulation_scene::systems::scene_cleanup_and_reload};
+
+    #[test]
+    fn loading_a_snapshot_restores_the_clock() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .add_plugin(AssetPlugin)
+            .insert_resource(DebugLines::default())
+            .init_resource::<TrailBuffers>()
+            .insert_resource(SimulationTime(5.0))
+            .insert_resource(IntegrationParameters::default())
+            .insert_resource(LoadedScene::new("empty", Empty))
+            .add_system_to_stage(CoreStage::PreUpdate, scene_cleanup_and_reload)
+            .add_system(advance_time);
+        app.update();
+
+        let snapshot = SnapshotFile::from_ron(
+            r#"(version: 2, time: 120.0, scene: (id: "empty"), bodies: [])"#,
+        )
+        .unwrap();
+        let restored = RestoredScene::new(snapshot, &SceneCollection::new()).unwrap();
+        app.world
+            .resource_mut::<LoadedScene>()
+            .load("empty", Box::new(restored));
+        app.update();
+
+        let dt = app.world.resource::<IntegrationParameters>().dt as f64;
+        assert_eq!(app.world.resource::<SimulationTime>().0, 120.0 + dt);
+    }
+}
@@ -0,0 +1,119 @@
+//! Physical data and mean orbital elements at the J2000 epoch.
+//!
+//! Planetary elements are those of Standish's approximate positions of the major planets, Earth's
+//! being those of the Earth-Moon barycentre. Orbits are flattened onto the ecliptic.
+
+use bevy::prelude::Color;
+
+/// Masses in kilograms and radii in kilometres.
+pub struct Body {
+    pub name: &'static str,
+    pub mass: f64,
+    pub radius: f64,
+    pub color: Color,
+}
+
+/// Elliptic orbit in the plane of the ecliptic, angles in degrees.
+pub struct Orbit {
+    pub semi_major_axis: f64,
+    pub eccentricity: f64,
+    pub longitude_of_periapsis: f64,
+    pub mean_longitude: f64,
+}
+
+/// A planet, with the semi-major axis of its orbit in astronomical units.
+pub struct Planet {
+    pub body: Body,
+    pub orbit: Orbit,
+}
+
+/// A satellite, with the semi-major axis of its orbit in kilometres.
+pub struct Satellite {
+    pub body: Body,
+    pub planet: &'static str,
+    pub orbit: Orbit,
+}
+
+pub const SUN: Body = Body {
+    name: "Sun",
+    mass: 1.988_47E30,
+    radius: 695_700.0,
+    color: Color::rgb(1.0, 0.9, 0.6),
+};
+
+const fn planet(
+    name: &'static str,
+    mass: f64,
+    radius: f64,
+    color: Color,
+    [semi_major_axis, eccentricity, longitude_of_periapsis, mean_longitude]: [f64; 4],
+) -> Planet {
+    Planet {
+        body: Body {
+            name,
+            mass,
+            radius,
+            color,
+        },
+        orbit: Orbit {
+            semi_major_axis,
+            eccentricity,
+            longitude_of_periapsis,
+            mean_longitude,
+        },
+    }
+}
+
+#[rustfmt::skip]
+pub const PLANETS: [Planet; 8] = [
+    planet("Mercury", 3.3011E23, 2_439.7, Color::rgb(0.6, 0.6, 0.6),
+        [0.387_099_27, 0.205_635_93, 77.457_796, 252.250_324]),
+    planet("Venus", 4.8675E24, 6_051.8, Color::rgb(0.9, 0.8, 0.6),
+        [0.723_335_66, 0.006_776_72, 131.602_467, 181.979_100]),
+    planet("Earth", 5.9722E24, 6_371.0, Color::rgb(0.3, 0.5, 1.0),
+        [1.000_002_61, 0.016_711_23, 102.937_682, 100.464_572]),
+    planet("Mars", 6.4171E23, 3_389.5, Color::rgb(0.9, 0.4, 0.2),
+        [1.523_710_34, 0.093_394_10, -23.943_630, -4.553_432]),
+    planet("Jupiter", 1.898_19E27, 69_911.0, Color::rgb(0.85, 0.7, 0.55),
+        [5.202_887_00, 0.048_386_24, 14.728_480, 34.396_441]),
+    planet("Saturn", 5.6834E26, 58_232.0, Color::rgb(0.9, 0.85, 0.6),
+        [9.536_675_94, 0.053_861_79, 92.598_878, 49.954_244]),
+    planet("Uranus", 8.6813E25, 25_362.0, Color::rgb(0.6, 0.85, 0.9),
+        [19.189_164_64, 0.047_257_44, 170.954_276, 313.238_105]),
+    planet("Neptune", 1.024_13E26, 24_622.0, Color::rgb(0.35, 0.5, 0.95),
+        [30.069_922_76, 0.008_590_48, 44.964_762, -55.120_030]),
+];
+
+const fn satellite(
+    name: &'static str,
+    planet: &'static str,
+    mass: f64,
+    radius: f64,
+    [semi_major_axis, eccentricity, longitude_of_periapsis, mean_longitude]: [f64; 4],
+) -> Satellite {
+    Satellite {
+        body: Body {
+            name,
+            mass,
+            radius,
+            color: Color::rgb(0.8, 0.8, 0.8),
+        },
+        planet,
+        orbit: Orbit {
+            semi_major_axis,
+            eccentricity,
+            longitude_of_periapsis,
+            mean_longitude,
+        },
+    }
+}
+
+/// The satellites with known mean elements at the epoch, those of the Moon being referred to the
+/// ecliptic.
+#[rustfmt::skip]
+pub const SATELLITES: [Satellite; 1] = [
+    satellite("Moon", "Earth", 7.342E22, 1_737.4, [384_399.0, 0.0549, 83.353, 218.316]),
+];
+
+/// Semi-major axes of the main asteroid belt in astronomical units.
+pub const ASTEROID_BELT: std::ops::Range<f64> = 2.1..3.3;
@@ -0,0 +1,313 @@
+mod ephemeris;
+
+use std::f32::consts::{PI, TAU};
+
+use bevy::{
+    ecs::system::{EntityCommands, Res},
+    prelude::{AssetServer, BuildChildren, Color, Name, Vec2, Vec3},
+};
+use bevy_egui::egui::{ComboBox, Ui};
+use heron::Velocity;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+use self::ephemeris::{Body, Orbit, ASTEROID_BELT, PLANETS, SATELLITES, SUN};
+use crate::{
+    nbody::PointMass,
+    orbit::kepler,
+    reference_frame::FollowOnSpawn,
+    simulation_scene::{seed_picker, SceneParams, Spawnable},
+    trails::Trail,
+    units::{Units, ASTRONOMICAL_UNIT, DAY, KILOMETRE},
+    BodyBundle, SceneData, G,
+};
+
+/// Length units in an astronomical unit, so that the Moon orbits outside of a magnified Earth.
+const LENGTH_UNITS_PER_AU: f64 = 1E4;
+/// Smallest radius of a body, so that it stays visible.
+const MIN_RADIUS: f32 = 1.0;
+
+/// The Sun, the planets and the Moon at the J2000 epoch, in physical proportions.
+#[derive(Clone, SceneParams)]
+#[scene(name = "Solar system")]
+pub struct SolarSystem {
+    #[param(default = "Earth".to_string(), widget = pick_followed)]
+    follow: String,
+    #[param(default = true)]
+    moons: bool,
+    #[param(default = 300, range = 0..=2000)]
+    asteroids: usize,
+    /// Magnification of the radii, limited so that bodies stay clear of their satellites.
+    #[param(default = 10.0, range = 1.0..=1000.0, log)]
+    radius_scale: f32,
+    /// Beyond a few days per second, the Moon only takes a few steps per orbit.
+    #[param(default = 2.0, range = 0.1..=50.0, log)]
+    days_per_second: f32,
+    #[param(default = 0, widget = seed_picker)]
+    seed: u64,
+}
+
+/// A body of the system, in simulation units.
+struct SolarBody {
+    name: Option<&'static str>,
+    position: Vec3,
+    velocity: Vec3,
+    /// Zero for asteroids, which have no gravity.
+    mass: f32,
+    radius: f32,
+    color: Color,
+    period: Option<f32>,
+}
+
+fn pick_followed(ui: &mut Ui, selected: &mut String) {
+    ComboBox::from_id_source("follow")
+        .selected_text(selected.as_str())
+        .show_ui(ui, |ui| {
+            for name in ["None", SUN.name]
+                .into_iter()
+                .chain(PLANETS.iter().map(|p| p.body.name))
+            {
+                ui.selectable_value(selected, name.to_string(), name);
+            }
+        });
+    ui.label("Follow");
+}
+
+/// Position and velocity at the epoch relative to the primary, for a semi-major axis in simulation
+/// units and a gravitational parameter `mu` of the pair.
+fn orbit_state(orbit: &Orbit, semi_major_axis: f32, mu: f32) -> (Vec3, Vec3) {
+    let eccentricity = orbit.eccentricity as f32;
+    let periapsis = semi_major_axis * (1.0 - eccentricity);
+    let direction = Vec2::from_angle((orbit.longitude_of_periapsis as f32).to_radians());
+    let speed = (mu * (1.0 + eccentricity) / periapsis).sqrt();
+
+    let mean_anomaly = ((orbit.mean_longitude - orbit.longitude_of_periapsis) as f32)
+        .to_radians()
+        .rem_euclid(TAU);
+    let mean_motion = (mu / semi_major_axis.powi(3)).sqrt();
+
+    kepler::propagate(
+        (direction * periapsis).extend(0.0),
+        (direction.perp() * speed).extend(0.0),
+        mu,
+        mean_anomaly / mean_motion,
+    )
+}
+
+impl SolarSystem {
+    fn units(&self) -> Units {
+        Units::new(
+            ASTRONOMICAL_UNIT / LENGTH_UNITS_PER_AU,
+            self.days_per_second as f64 * DAY,
+        )
+    }
+
+    /// Magnified radius of `body`, at most half of `limit`.
+    fn radius(&self, body: &Body, limit: f32) -> f32 {
+        let radius = self.units().length(body.radius * KILOMETRE) * self.radius_scale;
+        radius.min(limit / 2.0).max(MIN_RADIUS)
+    }
+
+    /// All bodies, the Sun first, with the barycentre at rest at the origin.
+    fn bodies(&self) -> Vec<SolarBody> {
+        let units = self.units();
+        let sun_mass = units.mass(SUN.mass);
+        let mut bodies = vec![];
+        let mut closest_planet = f32::INFINITY;
+
+        for planet in &PLANETS {
+            let satellites: Vec<_> = SATELLITES
+                .iter()
+                .filter(|s| self.moons && s.planet == planet.body.name)
+                .collect();
+
+            let planet_mass = units.mass(planet.body.mass);
+            let system_mass = planet_mass
+                + satellites
+                    .iter()
+                    .map(|s| units.mass(s.body.mass))
+                    .sum::<f32>();
+            let semi_major_axis = units.length(planet.orbit.semi_major_axis * ASTRONOMICAL_UNIT);
+            let mu = G * (sun_mass + system_mass);
+            let (barycentre, barycentre_velocity) = orbit_state(&planet.orbit, semi_major_axis, mu);
+            closest_planet =
+                closest_planet.min(semi_major_axis * (1.0 - planet.orbit.eccentricity as f32));
+
+            let mut moons = vec![];
+            let mut closest_moon = f32::INFINITY;
+            for satellite in satellites {
+                let mass = units.mass(satellite.body.mass);
+                let semi_major_axis = units.length(satellite.orbit.semi_major_axis * KILOMETRE);
+                let mu = G * (planet_mass + mass);
+                let (position, velocity) = orbit_state(&satellite.orbit, semi_major_axis, mu);
+                closest_moon =
+                    closest_moon.min(semi_major_axis * (1.0 - satellite.orbit.eccentricity as f32));
+
+                moons.push(SolarBody {
+                    name: Some(satellite.body.name),
+                    position,
+                    velocity,
+                    mass,
+                    radius: self.radius(&satellite.body, f32::INFINITY),
+                    color: satellite.body.color,
+                    period: None,
+                });
+            }
+
+            // The elements are those of the barycentre of the planet and its moons.
+            let offset = moons
+                .iter()
+                .map(|m| m.position * m.mass)
+                .fold(Vec3::ZERO, |sum, v| sum + v)
+                / system_mass;
+            let velocity_offset = moons
+                .iter()
+                .map(|m| m.velocity * m.mass)
+                .fold(Vec3::ZERO, |sum, v| sum + v)
+                / system_mass;
+            let (position, velocity) = (barycentre - offset, barycentre_velocity - velocity_offset);
+
+            bodies.push(SolarBody {
+                name: Some(planet.body.name),
+                position,
+                velocity,
+                mass: planet_mass,
+                radius: self.radius(&planet.body, closest_moon),
+                color: planet.body.color,
+                period: Some(TAU * (semi_major_axis.powi(3) / mu).sqrt()),
+            });
+            bodies.extend(moons.into_iter().map(|moon| SolarBody {
+                position: position + moon.position,
+                velocity: velocity + moon.velocity,
+                ..moon
+            }));
+        }
+
+        let mut rng = ChaCha8Rng::seed_from_u64(self.seed);
+        for _ in 0..self.asteroids {
+            let orbit = Orbit {
+                semi_major_axis: rng.gen_range(ASTEROID_BELT),
+                eccentricity: rng.gen_range(0.0..0.2),
+                longitude_of_periapsis: rng.gen_range(0.0..360.0),
+                mean_longitude: rng.gen_range(0.0..360.0),
+            };
+            let semi_major_axis = units.length(orbit.semi_major_axis * ASTRONOMICAL_UNIT);
+            let (position, velocity) = orbit_state(&orbit, semi_major_axis, G * sun_mass);
+
+            bodies.push(SolarBody {
+                name: None,
+                position,
+                velocity,
+                mass: 0.0,
+                radius: MIN_RADIUS,
+                color: Color::rgb(0.5, 0.45, 0.4),
+                period: None,
+            });
+        }
+
+        bodies.insert(
+            0,
+            SolarBody {
+                name: Some(SUN.name),
+                position: Vec3::ZERO,
+                velocity: Vec3::ZERO,
+                mass: sun_mass,
+                radius: self.radius(&SUN, closest_planet),
+                color: SUN.color,
+                period: None,
+            },
+        );
+
+        let mass: f32 = bodies.iter().map(|b| b.mass).sum();
+        let barycentre = bodies
+            .iter()
+            .map(|b| b.position * b.mass)
+            .fold(Vec3::ZERO, |sum, v| sum + v)
+            / mass;
+        let velocity = bodies
+            .iter()
+            .map(|b| b.velocity * b.mass)
+            .fold(Vec3::ZERO, |sum, v| sum + v)
+            / mass;
+        for body in &mut bodies {
+            body.position -= barycentre;
+            body.velocity -= velocity;
+        }
+        bodies
+    }
+}
+
+impl SceneData for SolarSystem {
+    fn instance(&self, mut scene_commands: EntityCommands, asset_server: Res<AssetServer>) {
+        let bodies = self.bodies();
+
+        scene_commands.with_children(|child| {
+            for body in bodies {
+                let (physics_mass, point_mass) = if body.mass > 0.0 {
+                    (body.mass, PointMass::HasGravity { mass: body.mass })
+                } else {
+                    (0.01, PointMass::AffectedByGravity)
+                };
+
+                let mut commands = child.spawn_bundle(BodyBundle::new(
+                    body.position,
+                    Velocity::from_linear(body.velocity),
+                    physics_mass / (body.radius * body.radius * PI),
+                    physics_mass,
+                    point_mass,
+                    body.color,
+                    &asset_server,
+                ));
+                if let Some(name) = body.name {
+                    commands.insert(Name::new(name));
+                    if name == self.follow {
+                        commands.insert(FollowOnSpawn);
+                    }
+                }
+                if let Some(period) = body.period {
+                    commands.insert(Trail::new(period.clamp(5.0, 60.0)));
+                }
+            }
+        });
+    }
+
+    fn spawnable(&self) -> Spawnable {
+        let units = self.units();
+        let (moon, earth) = (&SATELLITES[0].body, &PLANETS[2].body);
+        let radius = self.radius(earth, f32::INFINITY);
+
+        Spawnable::Massive {
+            min_mass: units.mass(moon.mass),
+            max_mass: units.mass(earth.mass),
+            density: units.mass(earth.mass) / (radius * radius * PI),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn earth_starts_at_the_j2000_epoch() {
+        let scene = SolarSystem::default();
+        let bodies = scene.bodies();
+        let body = |name| bodies.iter().find(|b| b.name == Some(name)).unwrap();
+        let (sun, earth, moon) = (body("Sun"), body("Earth"), body("Moon"));
+
+        // The Earth-Moon barycentre lies 0.983 AU from the Sun at a longitude of 100.38° (the
+        // Sun appears at 280.38° from the Earth).
+        let barycentre = (earth.position * earth.mass + moon.position * moon.mass)
+            / (earth.mass + moon.mass)
+            - sun.position;
+        let distance = barycentre.length() / LENGTH_UNITS_PER_AU as f32;
+        assert!((distance - 0.9833).abs() < 1E-3);
+        let longitude = barycentre.y.atan2(barycentre.x).to_degrees();
+        assert!((longitude - 100.38).abs() < 0.1);
+
+        let momentum = bodies
+            .iter()
+            .fold(Vec3::ZERO, |sum, b| sum + b.velocity * b.mass);
+        assert!(momentum.length() < 1E-3 * sun.mass * sun.velocity.length());
+    }
+}
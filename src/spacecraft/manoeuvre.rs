@@ -0,0 +1,510 @@
+use bevy::prelude::*;
+use bevy_egui::{
+    egui::{DragValue, Grid, Slider, Window},
+    EguiContext,
+};
+use bevy_mouse_tracking_plugin::{MainCamera, MousePosWorld};
+use bevy_prototype_debug_lines::DebugLines;
+use heron::{rapier_plugin::rapier2d::prelude::IntegrationParameters, Velocity};
+use particular::prelude::*;
+
+use super::Spacecraft;
+use crate::{
+    nbody::{Body, Forecast, SimulationTime},
+    selection::{PointerCapture, Selection},
+    shapes::DebugShapes,
+};
+
+// The forecast only integrates the ship against the attractors pulling hardest on it.
+const MAX_ATTRACTORS: usize = 32;
+// Longest forecast, whatever the horizon and time step.
+const MAX_STEPS: usize = 10_000;
+// Fraction of the horizon after which a trajectory is predicted again.
+const REFRESH: f32 = 0.25;
+// Distance in pixels between a node and its handles.
+const HANDLE_OFFSET: f32 = 40.0;
+// Radius in pixels of a handle.
+const HANDLE_RADIUS: f32 = 6.0;
+// Δv per pixel the mouse moves along a dragged handle.
+const DRAG_SENSITIVITY: f32 = 0.5;
+
+/// A planned impulsive burn, in the prograde and radial directions relative to the dominant attractor.
+#[derive(Component, Clone, Copy, PartialEq)]
+pub struct ManoeuvreNode {
+    /// Simulation time of the burn.
+    pub time: f64,
+    pub prograde: f32,
+    pub radial: f32,
+}
+
+impl ManoeuvreNode {
+    fn delta_v(&self, (prograde, radial): (Vec3, Vec3)) -> Vec3 {
+        prograde * self.prograde + radial * self.radial
+    }
+
+    /// Total Δv of the burn.
+    fn cost(&self) -> f32 {
+        Vec2::new(self.prograde, self.radial).length()
+    }
+
+    /// Burns the fuel of `ship` and returns the change in velocity, scaled down if the tank runs dry.
+    fn burn(&self, ship: &mut Spacecraft, frame: (Vec3, Vec3)) -> Vec3 {
+        let cost = self.cost();
+        if cost <= 0.0 {
+            return Vec3::ZERO;
+        }
+        self.delta_v(frame) * ship.impulse(cost) / cost
+    }
+}
+
+/// A handle drawn around a node, dragged along its axis to edit one component of the burn.
+#[derive(Clone, Copy, PartialEq)]
+pub enum NodeHandle {
+    Prograde,
+    Retrograde,
+    RadialOut,
+    RadialIn,
+}
+
+impl NodeHandle {
+    const ALL: [Self; 4] = [
+        Self::Prograde,
+        Self::Retrograde,
+        Self::RadialOut,
+        Self::RadialIn,
+    ];
+
+    /// Direction of the handle from the node.
+    fn direction(self, (prograde, radial): (Vec3, Vec3)) -> Vec3 {
+        match self {
+            Self::Prograde => prograde,
+            Self::Retrograde => -prograde,
+            Self::RadialOut => radial,
+            Self::RadialIn => -radial,
+        }
+    }
+
+    /// Direction in which dragging the handle increases its component.
+    fn axis(self, (prograde, radial): (Vec3, Vec3)) -> Vec3 {
+        match self {
+            Self::Prograde | Self::Retrograde => prograde,
+            Self::RadialOut | Self::RadialIn => radial,
+        }
+    }
+
+    fn component(self, node: &mut ManoeuvreNode) -> &mut f32 {
+        match self {
+            Self::Prograde | Self::Retrograde => &mut node.prograde,
+            Self::RadialOut | Self::RadialIn => &mut node.radial,
+        }
+    }
+
+    fn color(self) -> Color {
+        match self {
+            Self::Prograde | Self::Retrograde => Color::GREEN,
+            Self::RadialOut | Self::RadialIn => Color::TEAL,
+        }
+    }
+
+    fn position(self, node: Vec3, frame: (Vec3, Vec3), scale: f32) -> Vec3 {
+        node + self.direction(frame) * HANDLE_OFFSET * scale
+    }
+}
+
+pub struct Planner {
+    /// Length of the predicted trajectory, in seconds.
+    pub horizon: f32,
+}
+
+impl Default for Planner {
+    fn default() -> Self {
+        Self { horizon: 20.0 }
+    }
+}
+
+/// Predicted trajectory of the selected ship, before and after its manoeuvre node.
+#[derive(Default)]
+pub struct Trajectory {
+    ship: Option<Entity>,
+    /// Node and horizon the trajectory was predicted for.
+    plan: Option<(Option<ManoeuvreNode>, f32)>,
+    start: f64,
+    /// Positions one step apart, from one step after `start`.
+    coast: Vec<Vec3>,
+    burn: Vec<Vec3>,
+    /// Index in `coast` of the first position after the burn.
+    burn_start: usize,
+    node: Option<(Vec3, (Vec3, Vec3))>,
+}
+
+/// The `count` attractors pulling hardest at `position`.
+fn strongest_attractors<'a>(
+    attractors: impl IntoIterator<Item = &'a Body>,
+    position: Vec3,
+    count: usize,
+) -> Vec<&'a Body> {
+    let pull = |body: &Body| body.mu() / body.position().distance_squared(position);
+    let mut attractors: Vec<_> = attractors.into_iter().collect();
+    attractors.sort_by(|a, b| pull(b).total_cmp(&pull(a)));
+    attractors.truncate(count);
+    attractors
+}
+
+/// Prograde and radial-out directions of a body relative to the attractor pulling hardest on it.
+fn burn_frame(
+    position: Vec3,
+    velocity: Vec3,
+    attractors: impl IntoIterator<Item = (Vec3, Vec3, f32)>,
+) -> (Vec3, Vec3) {
+    let primary = attractors
+        .into_iter()
+        .filter(|(other, ..)| *other != position)
+        .max_by(|(a, _, a_mu), (b, _, b_mu)| {
+            let pull = |other: &Vec3, mu: f32| mu / other.distance_squared(position);
+            pull(a, *a_mu).total_cmp(&pull(b, *b_mu))
+        });
+    let (position, velocity) = match primary {
+        Some((primary, primary_velocity, _)) => (position - primary, velocity - primary_velocity),
+        None => (position, velocity),
+    };
+
+    let prograde = velocity.try_normalize().unwrap_or(Vec3::X);
+    let radial = (position - prograde * position.dot(prograde))
+        .try_normalize()
+        .unwrap_or_else(|| prograde.cross(Vec3::Z));
+    (prograde, radial)
+}
+
+/// Predicts the trajectory again when the plan changes, the ship thrusts or the prediction runs out.
+#[allow(clippy::too_many_arguments)]
+pub fn predict_trajectory(
+    planner: Res<Planner>,
+    time: Res<SimulationTime>,
+    selection: Res<Selection>,
+    integration: Res<IntegrationParameters>,
+    particle_set: Res<ParticleSet<Body>>,
+    mut trajectory: ResMut<Trajectory>,
+    ships: Query<(
+        Entity,
+        &GlobalTransform,
+        &Spacecraft,
+        Option<&ManoeuvreNode>,
+    )>,
+    velocities: Query<&Velocity>,
+) {
+    let (entity, transform, ship, node) = match selection.body().and_then(|e| ships.get(e).ok()) {
+        Some(ship) => ship,
+        None => {
+            if trajectory.ship.is_some() {
+                *trajectory = Trajectory::default();
+            }
+            return;
+        }
+    };
+
+    let plan = (node.copied(), planner.horizon);
+    let elapsed = (time.0 - trajectory.start) as f32;
+    if trajectory.ship == Some(entity)
+        && trajectory.plan == Some(plan)
+        && !ship.is_thrusting()
+        && elapsed < planner.horizon * REFRESH
+    {
+        return;
+    }
+
+    let velocity = |e| velocities.get(e).map_or(Vec3::ZERO, |v| v.linear);
+    let dt = integration.dt;
+    let position = transform.translation();
+
+    let attractors = strongest_attractors(particle_set.massive(), position, MAX_ATTRACTORS);
+    let mut forecast = Forecast::new(attractors, velocity);
+    let coast = forecast.add_probe(entity, position, velocity(entity));
+    let burn = forecast.add_probe(entity, position, velocity(entity));
+
+    *trajectory = Trajectory {
+        ship: Some(entity),
+        plan: Some(plan),
+        start: time.0,
+        ..default()
+    };
+    let mut ship = *ship;
+    let mut pending = node.copied();
+    let steps = ((planner.horizon / dt) as usize).min(MAX_STEPS);
+    for step in 0..steps {
+        let now = time.0 + (step as f64 + 0.5) * dt as f64;
+        if let Some(node) = pending.filter(|node| now >= node.time) {
+            let (position, velocity) = forecast.probe(burn);
+            let frame = burn_frame(position, velocity, forecast.massive());
+
+            forecast.set_probe_velocity(burn, velocity + node.burn(&mut ship, frame));
+            trajectory.node = Some((position, frame));
+            trajectory.burn_start = step;
+            pending = None;
+        }
+
+        forecast.step(dt);
+        trajectory.coast.push(forecast.probe(coast).0);
+        if trajectory.node.is_some() {
+            trajectory.burn.push(forecast.probe(burn).0);
+        }
+    }
+}
+
+pub fn execute_nodes(
+    mut commands: Commands,
+    time: Res<SimulationTime>,
+    integration: Res<IntegrationParameters>,
+    particle_set: Res<ParticleSet<Body>>,
+    mut ships: Query<(
+        Entity,
+        &GlobalTransform,
+        &mut Spacecraft,
+        &mut Velocity,
+        &ManoeuvreNode,
+    )>,
+    attractors: Query<&Velocity, Without<Spacecraft>>,
+) {
+    let now = time.0 + 0.5 * integration.dt as f64;
+
+    for (entity, transform, mut ship, mut velocity, node) in ships.iter_mut() {
+        if now < node.time {
+            continue;
+        }
+
+        let attractors = particle_set.massive().filter_map(|body| {
+            let body_velocity = attractors.get(body.entity()).ok()?;
+            Some((body.position(), body_velocity.linear, body.mu()))
+        });
+        let frame = burn_frame(transform.translation(), velocity.linear, attractors);
+        velocity.linear += node.burn(&mut ship, frame);
+        commands.entity(entity).remove::<ManoeuvreNode>();
+    }
+}
+
+pub fn place_node(
+    mut commands: Commands,
+    mut egui_ctx: ResMut<EguiContext>,
+    keys: Res<Input<KeyCode>>,
+    mouse_pos: Res<MousePosWorld>,
+    integration: Res<IntegrationParameters>,
+    trajectory: Res<Trajectory>,
+    nodes: Query<&ManoeuvreNode>,
+) {
+    if egui_ctx.ctx_mut().wants_keyboard_input() {
+        return;
+    }
+    let ship = match trajectory.ship {
+        Some(ship) if keys.just_pressed(KeyCode::N) => ship,
+        _ => return,
+    };
+    let mouse_pos = mouse_pos.truncate().extend(0.0);
+
+    let nearest = trajectory
+        .coast
+        .iter()
+        .enumerate()
+        .min_by(|(_, a), (_, b)| {
+            a.distance_squared(mouse_pos)
+                .total_cmp(&b.distance_squared(mouse_pos))
+        })
+        .map(|(step, _)| step);
+
+    if let Some(step) = nearest {
+        let time = trajectory.start + (step + 1) as f64 * integration.dt as f64;
+        let node = match nodes.get(ship) {
+            Ok(node) => ManoeuvreNode { time, ..*node },
+            Err(_) => ManoeuvreNode {
+                time,
+                prograde: 0.0,
+                radial: 0.0,
+            },
+        };
+        commands.entity(ship).insert(node);
+    }
+}
+
+/// Drags the handles of the node of the selected ship with the left mouse button.
+#[allow(clippy::too_many_arguments)]
+pub fn drag_handles(
+    mut dragged: Local<Option<(NodeHandle, Vec3)>>,
+    mut capture: ResMut<PointerCapture>,
+    mut egui_ctx: ResMut<EguiContext>,
+    buttons: Res<Input<MouseButton>>,
+    mouse_pos: Res<MousePosWorld>,
+    camera: Query<&OrthographicProjection, With<MainCamera>>,
+    trajectory: Res<Trajectory>,
+    mut nodes: Query<&mut ManoeuvreNode>,
+) {
+    let scale = camera.get_single().map_or(1.0, |proj| proj.scale);
+    let mouse_pos = mouse_pos.truncate().extend(0.0);
+    let node = trajectory.ship.zip(trajectory.node);
+
+    if !buttons.pressed(MouseButton::Left) {
+        *dragged = None;
+    }
+
+    if let Some((handle, last)) = dragged.as_mut() {
+        if let Some((ship, (_, frame))) = node {
+            if let Ok(mut node) = nodes.get_mut(ship) {
+                let moved = (mouse_pos - *last) / scale;
+                *handle.component(&mut node) += moved.dot(handle.axis(frame)) * DRAG_SENSITIVITY;
+            }
+        }
+        *last = mouse_pos;
+        capture.0 = true;
+        return;
+    }
+
+    let hovered = node
+        .filter(|_| !egui_ctx.ctx_mut().wants_pointer_input())
+        .and_then(|(_, (position, frame))| {
+            NodeHandle::ALL.into_iter().find(|handle| {
+                let distance = handle.position(position, frame, scale).distance(mouse_pos);
+                distance <= HANDLE_RADIUS * scale
+            })
+        });
+    capture.0 = hovered.is_some();
+
+    if let Some(handle) = hovered.filter(|_| buttons.just_pressed(MouseButton::Left)) {
+        *dragged = Some((handle, mouse_pos));
+    }
+}
+
+pub fn planner_window(
+    mut commands: Commands,
+    mut egui_ctx: ResMut<EguiContext>,
+    mut planner: ResMut<Planner>,
+    time: Res<SimulationTime>,
+    selection: Res<Selection>,
+    mut ships: Query<(&Spacecraft, Option<&mut ManoeuvreNode>)>,
+) {
+    let ship = selection.body();
+    let (spacecraft, node) = match ship.and_then(|e| ships.get_mut(e).ok()) {
+        Some(ship) => ship,
+        None => return,
+    };
+
+    Window::new("Manoeuvre").show(egui_ctx.ctx_mut(), |ui| {
+        ui.add(Slider::new(&mut planner.horizon, 1.0..=120.0).text("Prediction (s)"));
+        ui.separator();
+
+        let mut node = match node {
+            Some(node) => node,
+            None => {
+                ui.label("Press N to place a node on the predicted trajectory nearest the cursor.");
+                return;
+            }
+        };
+
+        Grid::new("manoeuvre_node").num_columns(2).show(ui, |ui| {
+            ui.label("Time to burn");
+            ui.label(format!("{:.1} s", node.time - time.0));
+            ui.end_row();
+
+            ui.label("Prograde");
+            ui.add(DragValue::new(&mut node.prograde).speed(0.5));
+            ui.end_row();
+
+            ui.label("Radial");
+            ui.add(DragValue::new(&mut node.radial).speed(0.5));
+            ui.end_row();
+
+            ui.label("Total Δv");
+            ui.label(format!("{:.1} / {:.1}", node.cost(), spacecraft.delta_v()));
+            ui.end_row();
+        });
+
+        if ui.button("Delete node").clicked() {
+            commands.entity(ship.unwrap()).remove::<ManoeuvreNode>();
+        }
+        ui.label("Drag the handles around the node to edit the burn.");
+        ui.label("N: move the node to the cursor");
+    });
+}
+
+pub fn draw_trajectory(
+    mut lines: ResMut<DebugLines>,
+    trajectory: Res<Trajectory>,
+    time: Res<SimulationTime>,
+    integration: Res<IntegrationParameters>,
+    camera: Query<&OrthographicProjection, With<MainCamera>>,
+) {
+    // The trajectory is only predicted now and then, so the part already flown is skipped.
+    let flown = ((time.0 - trajectory.start) / integration.dt as f64).max(0.0) as usize;
+    for (path, skip, color) in [
+        (&trajectory.coast, flown, Color::rgba(0.6, 0.6, 0.6, 0.5)),
+        (
+            &trajectory.burn,
+            flown.saturating_sub(trajectory.burn_start),
+            Color::ORANGE,
+        ),
+    ] {
+        for segment in path.get(skip..).unwrap_or_default().windows(2) {
+            lines.line_colored(segment[0], segment[1], 0.0, color);
+        }
+    }
+
+    if let Some((position, frame)) = trajectory.node {
+        let scale = camera.get_single().map_or(1.0, |proj| proj.scale);
+        lines.circle(position, 3.0 * scale, Color::CYAN);
+        for handle in NodeHandle::ALL {
+            let handle_position = handle.position(position, frame, scale);
+            lines.line_colored(position, handle_position, 0.0, handle.color());
+            lines.circle(handle_position, HANDLE_RADIUS * scale, handle.color());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn burn_frame_is_relative_to_dominant_attractor() {
+        let attractors = [
+            (Vec3::new(100.0, 0.0, 0.0), Vec3::new(0.0, 50.0, 0.0), 1E5),
+            (Vec3::new(-5000.0, 0.0, 0.0), Vec3::ZERO, 1E5),
+        ];
+        // Below the near attractor, moving along -x relative to it.
+        let (prograde, radial) = burn_frame(
+            Vec3::new(100.0, -10.0, 0.0),
+            Vec3::new(-1.0, 50.0, 0.0),
+            attractors,
+        );
+
+        assert!(prograde.distance(Vec3::NEG_X) < 1E-5);
+        assert!(radial.distance(Vec3::NEG_Y) < 1E-5);
+    }
+
+    #[test]
+    fn forecast_keeps_the_strongest_attractors() {
+        let bodies = [
+            Body::new(Vec3::new(1000.0, 0.0, 0.0), 5E7, Entity::from_raw(0)),
+            Body::new(Vec3::new(10.0, 0.0, 0.0), 1E3, Entity::from_raw(1)),
+            Body::new(Vec3::new(-100.0, 0.0, 0.0), 1E6, Entity::from_raw(2)),
+        ];
+
+        let strongest = strongest_attractors(&bodies, Vec3::ZERO, 2);
+        let entities: Vec<_> = strongest.iter().map(|body| body.entity().id()).collect();
+
+        assert_eq!(entities, [2, 0]);
+    }
+
+    #[test]
+    fn dry_ship_burns_part_of_the_node() {
+        let mut ship = Spacecraft::default();
+        let available = ship.delta_v();
+        let node = ManoeuvreNode {
+            time: 0.0,
+            prograde: 3.0 * available,
+            radial: 4.0 * available,
+        };
+
+        let delta_v = node.burn(&mut ship, (Vec3::X, Vec3::Y));
+
+        // A fifth of the node is burnt, in the planned direction.
+        let expected = Vec3::new(0.6, 0.8, 0.0) * available;
+        assert!(delta_v.distance(expected) < 1E-3 * available);
+        assert!(ship.fuel < 1E-6);
+    }
+}
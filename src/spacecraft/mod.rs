@@ -0,0 +1,256 @@
+mod manoeuvre;
+
+use std::f32::consts::PI;
+
+use bevy::prelude::*;
+use bevy_egui::{
+    egui::{Grid, Window},
+    EguiContext,
+};
+use bevy_prototype_debug_lines::DebugLines;
+use heron::{
+    rapier_plugin::rapier2d::prelude::IntegrationParameters, should_run, Acceleration,
+    CollisionShape,
+};
+
+use crate::{
+    nbody::{accelerate_particles, advance_time},
+    selection::{body_label, Selection},
+};
+
+// Radians per second the ship turns while A or D is held.
+const TURN_RATE: f32 = PI;
+// Throttle change per second while W or S is held.
+const THROTTLE_RATE: f32 = 0.5;
+
+pub struct SpacecraftPlugin;
+
+impl Plugin for SpacecraftPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<manoeuvre::Planner>()
+            .init_resource::<manoeuvre::Trajectory>()
+            .add_system(steer_spacecraft)
+            .add_system(spacecraft_window)
+            .add_system(draw_spacecraft)
+            .add_system(manoeuvre::planner_window)
+            .add_system(manoeuvre::place_node)
+            .add_system(manoeuvre::draw_trajectory)
+            .add_system_to_stage(CoreStage::PreUpdate, manoeuvre::drag_handles)
+            .add_system(manoeuvre::predict_trajectory)
+            .add_system_set_to_stage(
+                CoreStage::Update,
+                SystemSet::new()
+                    .with_run_criteria(should_run)
+                    .with_system(thrust_spacecraft.after(accelerate_particles))
+                    .with_system(
+                        manoeuvre::execute_nodes
+                            .after(accelerate_particles)
+                            .before(advance_time),
+                    ),
+            );
+    }
+}
+
+/// A massless body with a rocket engine, steered with the keyboard while selected.
+#[derive(Component, Clone, Copy)]
+pub struct Spacecraft {
+    pub dry_mass: f32,
+    pub fuel: f32,
+    pub exhaust_velocity: f32,
+    pub max_thrust: f32,
+    /// Direction of the thrust, in radians from the x axis.
+    pub heading: f32,
+    /// Fraction of the maximum thrust, between 0 and 1.
+    pub throttle: f32,
+}
+
+impl Default for Spacecraft {
+    fn default() -> Self {
+        Self {
+            dry_mass: 1.0,
+            fuel: 2.0,
+            exhaust_velocity: 300.0,
+            max_thrust: 100.0,
+            heading: PI / 2.0,
+            throttle: 0.0,
+        }
+    }
+}
+
+impl Spacecraft {
+    pub fn mass(&self) -> f32 {
+        self.dry_mass + self.fuel
+    }
+
+    /// Velocity change left with the remaining fuel, from the Tsiolkovsky rocket equation.
+    pub fn delta_v(&self) -> f32 {
+        self.exhaust_velocity * (self.mass() / self.dry_mass).ln()
+    }
+
+    pub fn direction(&self) -> Vec3 {
+        Vec3::new(self.heading.cos(), self.heading.sin(), 0.0)
+    }
+
+    pub fn is_thrusting(&self) -> bool {
+        self.throttle > 0.0 && self.fuel > 0.0
+    }
+
+    /// Burns fuel for `dt` at the current throttle and returns the resulting acceleration.
+    pub fn burn(&mut self, dt: f32) -> Vec3 {
+        if !self.is_thrusting() {
+            return Vec3::ZERO;
+        }
+
+        let thrust = self.max_thrust * self.throttle;
+        // Cut the burn short when the tank runs dry during the step.
+        let burnt = (thrust * dt / self.exhaust_velocity).min(self.fuel);
+        let acceleration = self.exhaust_velocity * burnt / dt / self.mass();
+        self.fuel -= burnt;
+
+        self.direction() * acceleration
+    }
+
+    /// Burns fuel for an instantaneous velocity change of `delta_v` and returns the change achieved.
+    pub fn impulse(&mut self, delta_v: f32) -> f32 {
+        let delta_v = delta_v.min(self.delta_v());
+        let mass = self.mass() * (-delta_v / self.exhaust_velocity).exp();
+        self.fuel = (mass - self.dry_mass).max(0.0);
+        delta_v
+    }
+}
+
+fn steer_spacecraft(
+    keys: Res<Input<KeyCode>>,
+    time: Res<Time>,
+    mut egui_ctx: ResMut<EguiContext>,
+    selection: Res<Selection>,
+    mut ships: Query<&mut Spacecraft>,
+) {
+    if egui_ctx.ctx_mut().wants_keyboard_input() {
+        return;
+    }
+
+    let mut ship = match selection.body().and_then(|e| ships.get_mut(e).ok()) {
+        Some(ship) => ship,
+        None => return,
+    };
+    let dt = time.delta_seconds();
+
+    if keys.pressed(KeyCode::A) {
+        ship.heading += TURN_RATE * dt;
+    }
+    if keys.pressed(KeyCode::D) {
+        ship.heading -= TURN_RATE * dt;
+    }
+    if keys.pressed(KeyCode::W) {
+        ship.throttle = (ship.throttle + THROTTLE_RATE * dt).min(1.0);
+    }
+    if keys.pressed(KeyCode::S) {
+        ship.throttle = (ship.throttle - THROTTLE_RATE * dt).max(0.0);
+    }
+    if keys.just_pressed(KeyCode::X) {
+        ship.throttle = 0.0;
+    }
+}
+
+fn thrust_spacecraft(
+    integration: Res<IntegrationParameters>,
+    mut ships: Query<(&mut Spacecraft, &mut Acceleration)>,
+) {
+    for (mut ship, mut acceleration) in ships.iter_mut() {
+        if ship.is_thrusting() {
+            acceleration.linear += ship.burn(integration.dt);
+        }
+    }
+}
+
+fn draw_spacecraft(
+    mut lines: ResMut<DebugLines>,
+    ships: Query<(&GlobalTransform, &CollisionShape, &Spacecraft)>,
+) {
+    for (transform, shape, ship) in ships.iter() {
+        let radius = match shape {
+            CollisionShape::Sphere { radius } => *radius,
+            _ => 1.0,
+        };
+        let position = transform.translation();
+        let direction = ship.direction();
+
+        lines.line_colored(
+            position,
+            position + direction * radius * 3.0,
+            0.0,
+            Color::WHITE,
+        );
+        if ship.is_thrusting() {
+            lines.line_colored(
+                position - direction * radius,
+                position - direction * radius * (1.0 + 4.0 * ship.throttle),
+                0.0,
+                Color::ORANGE_RED,
+            );
+        }
+    }
+}
+
+fn spacecraft_window(
+    mut egui_ctx: ResMut<EguiContext>,
+    selection: Res<Selection>,
+    ships: Query<(Entity, &Spacecraft, Option<&Name>)>,
+) {
+    let (entity, ship, name) = match selection.body().and_then(|e| ships.get(e).ok()) {
+        Some(ship) => ship,
+        None => return,
+    };
+
+    Window::new("Spacecraft").show(egui_ctx.ctx_mut(), |ui| {
+        ui.label(body_label(entity, name));
+        Grid::new("spacecraft")
+            .num_columns(2)
+            .striped(true)
+            .show(ui, |ui| {
+                ui.label("Throttle");
+                ui.label(format!("{:.0}%", ship.throttle * 100.0));
+                ui.end_row();
+
+                ui.label("Heading");
+                ui.label(format!(
+                    "{:.0}°",
+                    ship.heading.to_degrees().rem_euclid(360.0)
+                ));
+                ui.end_row();
+
+                ui.label("Fuel");
+                ui.label(format!("{:.2}", ship.fuel));
+                ui.end_row();
+
+                ui.label("Δv left");
+                ui.label(format!("{:.1}", ship.delta_v()));
+                ui.end_row();
+            });
+        ui.label("W/S: throttle, A/D: turn, X: cut engine");
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn full_burn_matches_rocket_equation() {
+        let mut ship = Spacecraft {
+            throttle: 1.0,
+            ..default()
+        };
+        let delta_v = ship.delta_v();
+
+        let dt = 1.0 / 600.0;
+        let mut gained = 0.0;
+        while ship.is_thrusting() {
+            gained += ship.burn(dt).length() * dt;
+        }
+
+        assert_eq!(ship.fuel, 0.0);
+        assert!((gained - delta_v).abs() < 1E-2 * delta_v);
+    }
+}
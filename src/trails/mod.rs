@@ -0,0 +1,500 @@
+mod ribbon;
+
+use std::collections::VecDeque;
+
+use bevy::{
+    prelude::*,
+    render::{
+        render_resource::{Extent3d, TextureDimension, TextureFormat},
+        view::NoFrustumCulling,
+    },
+    sprite::MaterialMesh2dBundle,
+    utils::HashMap,
+};
+use bevy_inspector_egui::Inspectable;
+use bevy_prototype_debug_lines::{DebugLines, DebugLinesPlugin};
+use heron::{should_run, Acceleration, Velocity};
+use serde::{Deserialize, Serialize};
+
+use self::ribbon::Ribbon;
+use crate::{
+    nbody::SimulationTime,
+    reference_frame::{frame_origin, update_frame, Bodies, FrameTransform, ReferenceFrame},
+};
+
+/// Trail samples per entity, keyed by the full [`Entity`] so that recycled ids start afresh.
+pub type TrailBuffers = HashMap<Entity, TrailBuffer>;
+
+pub struct TrailsPlugin;
+
+impl Plugin for TrailsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugin(DebugLinesPlugin::default())
+            .insert_resource(TrailBuffers::default())
+            .add_startup_system(spawn_ribbons)
+            .add_system(changed)
+            .add_system_to_stage(
+                CoreStage::PostUpdate,
+                reanchor_trails.after(update_frame).before(draw_trails),
+            )
+            .add_system_set_to_stage(
+                CoreStage::PostUpdate,
+                SystemSet::new()
+                    .with_run_criteria(should_run)
+                    .with_system(record_trails.before(draw_trails)),
+            )
+            .add_system_to_stage(CoreStage::PostUpdate, draw_trails);
+    }
+}
+
+/// Colours between `low` and `high` as a magnitude goes from zero to `max`.
+#[derive(Clone, Copy, Debug, PartialEq, Inspectable, Serialize, Deserialize)]
+pub struct Gradient {
+    pub low: Color,
+    pub high: Color,
+    pub max: f32,
+}
+
+impl Default for Gradient {
+    fn default() -> Self {
+        Self {
+            low: Color::BLUE,
+            high: Color::RED,
+            max: 200.0,
+        }
+    }
+}
+
+impl Gradient {
+    pub fn sample(&self, magnitude: f32) -> Color {
+        let t = (magnitude / self.max).clamp(0.0, 1.0);
+        let (low, high) = (Vec4::from(self.low), Vec4::from(self.high));
+        low.lerp(high, t).into()
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Inspectable, Serialize, Deserialize)]
+pub enum TrailColor {
+    /// The colour of the body's sprite.
+    Body,
+    Speed(Gradient),
+    Acceleration(Gradient),
+}
+
+/// What the positions of a trail are recorded relative to.
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+pub enum TrailAnchor {
+    /// The reference frame of the view.
+    #[default]
+    View,
+    World,
+    Body(Entity),
+    Barycentre,
+}
+
+impl TrailAnchor {
+    /// Origin of the anchor in world coordinates, or `None` if its body is gone.
+    pub fn origin(self, bodies: &Bodies) -> Option<Vec3> {
+        match self {
+            TrailAnchor::View | TrailAnchor::World => Some(Vec3::ZERO),
+            TrailAnchor::Body(entity) => frame_origin(ReferenceFrame::Body(entity), bodies),
+            TrailAnchor::Barycentre => frame_origin(ReferenceFrame::Barycentric, bodies),
+        }
+    }
+
+    /// Anchors to `new` in place of `old`, a body that was respawned.
+    pub fn remap(&mut self, old: Entity, new: Entity) {
+        if *self == TrailAnchor::Body(old) {
+            *self = TrailAnchor::Body(new);
+        }
+    }
+}
+
+#[derive(Component, Clone, Inspectable)]
+pub struct Trail {
+    /// Age in seconds of simulation time after which samples are dropped.
+    pub length: f32,
+    /// A sample is taken when the heading turns by more than this angle, in radians.
+    pub max_angle: f32,
+    /// A sample is taken at least every this distance, even on straight paths.
+    pub max_distance: f32,
+    /// Largest number of samples kept.
+    pub max_points: usize,
+    /// World-space width of the ribbon, or a hairline if zero.
+    pub width: f32,
+    /// Fraction of the trail over which the ribbon narrows at each end.
+    pub taper: f32,
+    pub color: TrailColor,
+    #[inspectable(ignore)]
+    pub anchor: TrailAnchor,
+}
+
+impl Trail {
+    pub fn new(length: f32) -> Self {
+        Self {
+            length,
+            max_angle: 3.0_f32.to_radians(),
+            max_distance: 20.0,
+            max_points: 2048,
+            width: 0.0,
+            taper: 0.2,
+            color: TrailColor::Body,
+            anchor: TrailAnchor::View,
+        }
+    }
+
+    pub fn with_color(mut self, color: TrailColor) -> Self {
+        self.color = color;
+        self
+    }
+
+    pub fn with_anchor(mut self, anchor: TrailAnchor) -> Self {
+        self.anchor = anchor;
+        self
+    }
+
+    pub fn with_width(mut self, width: f32) -> Self {
+        self.width = width;
+        self
+    }
+
+    fn segment_color(&self, sprite: Option<&Sprite>, speed: f32, acceleration: f32) -> Color {
+        match self.color {
+            TrailColor::Body => sprite.map_or(Color::WHITE, |sprite| sprite.color),
+            TrailColor::Speed(gradient) => gradient.sample(speed),
+            TrailColor::Acceleration(gradient) => gradient.sample(acceleration),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TrailSample {
+    pub time: f64,
+    pub position: Vec3,
+    pub color: Color,
+}
+
+/// Samples of a trail, oldest first, bounded by the point budget and maximum age of its [`Trail`].
+///
+/// Positions are relative to the origin of the anchor they were recorded with.
+#[derive(Default)]
+pub struct TrailBuffer {
+    samples: VecDeque<TrailSample>,
+    anchor: TrailAnchor,
+}
+
+impl TrailBuffer {
+    pub fn from_samples(
+        samples: impl IntoIterator<Item = TrailSample>,
+        anchor: TrailAnchor,
+    ) -> Self {
+        Self {
+            samples: samples.into_iter().collect(),
+            anchor,
+        }
+    }
+
+    pub fn samples(&self) -> impl Iterator<Item = &TrailSample> {
+        self.samples.iter()
+    }
+
+    pub fn anchor(&self) -> TrailAnchor {
+        self.anchor
+    }
+
+    pub fn push(&mut self, sample: TrailSample, trail: &Trail) {
+        while self.samples.len() >= trail.max_points.max(1) {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(sample);
+        self.prune(sample.time, trail);
+    }
+
+    /// Whether the path has turned or run far enough since the last sample to record `position`.
+    pub fn needs_sample(&self, position: Vec3, trail: &Trail) -> bool {
+        let mut samples = self.samples.iter().rev();
+        let last = match samples.next() {
+            Some(last) => last.position,
+            None => return true,
+        };
+        let offset = position - last;
+        let distance = offset.length();
+
+        match samples.next() {
+            // Until there is a heading, a short first segment gives one.
+            None => distance >= 0.1 * trail.max_distance,
+            Some(previous) => {
+                let heading = last - previous.position;
+                distance >= trail.max_distance
+                    || (distance > 0.0 && heading.angle_between(offset) > trail.max_angle)
+            }
+        }
+    }
+
+    /// Drops the samples older than the length of the trail.
+    pub fn prune(&mut self, now: f64, trail: &Trail) {
+        let oldest = now - trail.length as f64;
+        while self.samples.front().is_some_and(|s| s.time < oldest) {
+            self.samples.pop_front();
+        }
+    }
+}
+
+fn changed(mut buffers: ResMut<TrailBuffers>, removed: RemovedComponents<Trail>) {
+    for entity in removed.iter() {
+        buffers.remove(&entity);
+    }
+}
+
+// Moves the samples anchored to the view along with the reference frame, so they are expressed in it.
+fn reanchor_trails(frame: Res<FrameTransform>, mut buffers: ResMut<TrailBuffers>) {
+    if frame.displacement == Vec3::ZERO && frame.rotation == 0.0 {
+        return;
+    }
+
+    for sample in buffers
+        .values_mut()
+        .filter(|buffer| buffer.anchor == TrailAnchor::View)
+        .flat_map(|buffer| buffer.samples.iter_mut())
+    {
+        sample.position = frame.reanchor(sample.position);
+    }
+}
+
+type TrailBodies<'w, 's> = Query<
+    'w,
+    's,
+    (
+        Entity,
+        &'static GlobalTransform,
+        &'static Trail,
+        Option<&'static Sprite>,
+        Option<&'static Velocity>,
+        Option<&'static Acceleration>,
+    ),
+>;
+
+fn record_trails(
+    time: Res<SimulationTime>,
+    mut buffers: ResMut<TrailBuffers>,
+    query: TrailBodies,
+    bodies: Bodies,
+) {
+    let now = time.0;
+
+    for (entity, transform, trail, sprite, velocity, acceleration) in query.iter() {
+        let buffer = buffers.entry(entity).or_default();
+        if buffer.anchor != trail.anchor {
+            *buffer = TrailBuffer {
+                anchor: trail.anchor,
+                ..default()
+            };
+        }
+        let origin = match trail.anchor.origin(&bodies) {
+            Some(origin) => origin,
+            None => continue,
+        };
+
+        let position = transform.translation() - origin;
+        if buffer.needs_sample(position, trail) {
+            let color = trail.segment_color(
+                sprite,
+                velocity.map_or(0.0, |v| v.linear.length()),
+                acceleration.map_or(0.0, |a| a.linear.length()),
+            );
+            buffer.push(
+                TrailSample {
+                    time: now,
+                    position,
+                    color,
+                },
+                trail,
+            );
+        }
+    }
+}
+
+struct RibbonMesh(Handle<Mesh>);
+
+fn spawn_ribbons(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    let mesh = meshes.add(Ribbon::default().mesh());
+    // Colour materials only apply vertex colours along with a texture.
+    let texture = images.add(Image::new_fill(
+        Extent3d {
+            width: 1,
+            height: 1,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        &[255; 4],
+        TextureFormat::Rgba8UnormSrgb,
+    ));
+
+    commands
+        .spawn_bundle(MaterialMesh2dBundle {
+            mesh: mesh.clone().into(),
+            material: materials.add(ColorMaterial::from(texture)),
+            transform: Transform::from_xyz(0.0, 0.0, -1.0),
+            ..default()
+        })
+        .insert(NoFrustumCulling);
+    commands.insert_resource(RibbonMesh(mesh));
+}
+
+// Draws each trail around the current origin of its anchor, up to the current position.
+// Samples fade linearly with their age in simulation time, so trails hold still while paused.
+#[allow(clippy::too_many_arguments)]
+fn draw_trails(
+    time: Res<SimulationTime>,
+    mut lines: ResMut<DebugLines>,
+    mut buffers: ResMut<TrailBuffers>,
+    mut ribbon: Local<Ribbon>,
+    ribbon_mesh: Res<RibbonMesh>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    query: Query<(Entity, &GlobalTransform, &Trail)>,
+    bodies: Bodies,
+) {
+    let now = time.0;
+    ribbon.clear();
+
+    for (entity, transform, trail) in query.iter() {
+        let buffer = match buffers.get_mut(&entity) {
+            Some(buffer) if buffer.anchor == trail.anchor => buffer,
+            _ => continue,
+        };
+        let origin = match trail.anchor.origin(&bodies) {
+            Some(origin) => origin,
+            None => continue,
+        };
+        buffer.prune(now, trail);
+
+        let faded = |sample: &TrailSample| {
+            let age = ((now - sample.time) as f32 / trail.length).clamp(0.0, 1.0);
+            let mut color = sample.color;
+            color.set_a(color.a() * (1.0 - age));
+            color
+        };
+        let head = buffer.samples.back().map(|last| TrailSample {
+            position: transform.translation() - origin,
+            ..*last
+        });
+        let points = buffer
+            .samples()
+            .chain(head.as_ref())
+            .map(|sample| (origin + sample.position, faded(sample)));
+
+        if trail.width > 0.0 {
+            let points: Vec<_> = points
+                .map(|(position, color)| (position, trail.width, color))
+                .collect();
+            ribbon.add_strip(&points, trail.taper);
+        } else {
+            let points: Vec<_> = points.collect();
+            for segment in points.windows(2) {
+                let ((from, from_color), (to, to_color)) = (segment[0], segment[1]);
+                lines.line_gradient(from, to, 0.0, from_color, to_color);
+            }
+        }
+    }
+
+    if let Some(mesh) = meshes.get_mut(&ribbon_mesh.0) {
+        ribbon.write(mesh);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(time: f64) -> TrailSample {
+        TrailSample {
+            time,
+            position: Vec3::new(time as f32, 0.0, 0.0),
+            color: Color::WHITE,
+        }
+    }
+
+    #[test]
+    fn buffer_respects_point_budget_and_age() {
+        let trail = Trail {
+            max_points: 4,
+            ..Trail::new(10.0)
+        };
+        let mut buffer = TrailBuffer::default();
+
+        for time in 0..6 {
+            buffer.push(sample(time as f64), &trail);
+        }
+        let times: Vec<_> = buffer.samples().map(|s| s.time).collect();
+        assert_eq!(times, [2.0, 3.0, 4.0, 5.0]);
+
+        buffer.prune(13.5, &trail);
+        let times: Vec<_> = buffer.samples().map(|s| s.time).collect();
+        assert_eq!(times, [4.0, 5.0]);
+    }
+
+    #[test]
+    fn sampling_adapts_to_curvature() {
+        let trail = Trail::new(1000.0);
+        let record = |path: &dyn Fn(f32) -> Vec3| {
+            let mut buffer = TrailBuffer::default();
+            for step in 0..=1000 {
+                let position = path(step as f32 / 1000.0);
+                if buffer.needs_sample(position, &trail) {
+                    buffer.push(
+                        TrailSample {
+                            time: 0.0,
+                            position,
+                            ..sample(0.0)
+                        },
+                        &trail,
+                    );
+                }
+            }
+            buffer.samples.len()
+        };
+
+        // Both paths are about 630 units long.
+        let straight = record(&|t| Vec3::new(t * 630.0, 0.0, 0.0));
+        let circle = record(&|t| {
+            let angle = t * std::f32::consts::TAU;
+            Vec3::new(angle.cos(), angle.sin(), 0.0) * 100.0
+        });
+
+        assert!(straight <= 35);
+        assert!(circle >= 100);
+    }
+
+    #[test]
+    fn recycled_entity_does_not_inherit_trail() {
+        let mut world = World::new();
+        world.insert_resource(SimulationTime::default());
+        world.insert_resource(TrailBuffers::default());
+        let mut stage = SystemStage::single(record_trails);
+
+        let spawn = |world: &mut World, x: f32| {
+            world
+                .spawn()
+                .insert(GlobalTransform::from_xyz(x, 0.0, 0.0))
+                .insert(Trail::new(10.0))
+                .id()
+        };
+
+        let old = spawn(&mut world, 0.0);
+        stage.run(&mut world);
+        // Despawned without its removal being seen, as when a scene is torn down in one go.
+        world.despawn(old);
+        let new = spawn(&mut world, 500.0);
+        assert_eq!(new.id(), old.id());
+        stage.run(&mut world);
+
+        let buffers = world.resource::<TrailBuffers>();
+        let positions: Vec<_> = buffers[&new].samples().map(|s| s.position).collect();
+        assert_eq!(positions, [Vec3::new(500.0, 0.0, 0.0)]);
+    }
+}
@@ -0,0 +1,104 @@
+use bevy::{
+    prelude::*,
+    render::{mesh::Indices, render_resource::PrimitiveTopology},
+};
+
+/// Vertices of triangle strips of varying width, built on the CPU and uploaded as a single mesh.
+#[derive(Default)]
+pub struct Ribbon {
+    positions: Vec<[f32; 3]>,
+    colors: Vec<[f32; 4]>,
+    indices: Vec<u32>,
+}
+
+impl Ribbon {
+    /// Adds a strip through `points`, given as position, full width and colour.
+    ///
+    /// With a non-zero `taper`, the width narrows to zero over that fraction of the strip at both ends.
+    pub fn add_strip(&mut self, points: &[(Vec3, f32, Color)], taper: f32) {
+        if points.len() < 2 {
+            return;
+        }
+
+        let last = points.len() - 1;
+        for (index, &(position, width, color)) in points.iter().enumerate() {
+            let previous = points[index.saturating_sub(1)].0;
+            let next = points[(index + 1).min(last)].0;
+            let tangent = (next - previous).truncate().normalize_or_zero();
+            let normal = tangent.perp().extend(0.0);
+
+            let t = index as f32 / last as f32;
+            let taper = if taper > 0.0 {
+                (t.min(1.0 - t) / taper).min(1.0)
+            } else {
+                1.0
+            };
+            let offset = normal * width * taper * 0.5;
+
+            let base = self.positions.len() as u32;
+            self.positions.push((position - offset).into());
+            self.positions.push((position + offset).into());
+            self.colors.push(color.as_linear_rgba_f32());
+            self.colors.push(color.as_linear_rgba_f32());
+
+            if index > 0 {
+                self.indices
+                    .extend([base - 2, base - 1, base, base - 1, base + 1, base]);
+            }
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.positions.clear();
+        self.colors.clear();
+        self.indices.clear();
+    }
+
+    pub fn write(&self, mesh: &mut Mesh) {
+        // An empty vertex buffer cannot be bound, so an invisible triangle stands in for no trails.
+        let (positions, colors, indices) = if self.indices.is_empty() {
+            (vec![[0.0; 3]; 3], vec![[0.0; 4]; 3], vec![0, 1, 2])
+        } else {
+            (
+                self.positions.clone(),
+                self.colors.clone(),
+                self.indices.clone(),
+            )
+        };
+
+        let count = positions.len();
+        mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+        mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, vec![[0.0, 0.0, 1.0]; count]);
+        mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, vec![[0.5, 0.5]; count]);
+        mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, colors);
+        mesh.set_indices(Some(Indices::U32(indices)));
+    }
+
+    pub fn mesh(&self) -> Mesh {
+        let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
+        self.write(&mut mesh);
+        mesh
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strip_is_offset_across_its_direction() {
+        let mut ribbon = Ribbon::default();
+        let points: Vec<_> = (0..5)
+            .map(|i| (Vec3::new(i as f32 * 10.0, 0.0, 0.0), 2.0, Color::WHITE))
+            .collect();
+
+        ribbon.add_strip(&points, 0.5);
+
+        assert_eq!(ribbon.positions.len(), 10);
+        assert_eq!(ribbon.indices.len(), 4 * 6);
+        // Tapered to a point at the ends, full width in the middle.
+        assert_eq!(ribbon.positions[0], ribbon.positions[1]);
+        assert_eq!(ribbon.positions[4], [20.0, -1.0, 0.0]);
+        assert_eq!(ribbon.positions[5], [20.0, 1.0, 0.0]);
+    }
+}
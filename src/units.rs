@@ -0,0 +1,59 @@
+//! Conversion from physical units to the units of the simulation, in which the gravitational
+//! constant is [`G`].
+
+use crate::G;
+
+/// The gravitational constant in SI units.
+pub const GRAVITATIONAL_CONSTANT: f64 = 6.674_30E-11;
+pub const ASTRONOMICAL_UNIT: f64 = 1.495_978_707E11;
+pub const KILOMETRE: f64 = 1E3;
+pub const DAY: f64 = 86_400.0;
+
+/// Physical meaning of the units of the simulation.
+///
+/// A unit of length stands for `metres` and a simulated second for `seconds`. With [`G`] fixed,
+/// these two determine the unit of mass.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Units {
+    metres: f64,
+    seconds: f64,
+}
+
+impl Units {
+    pub fn new(metres: f64, seconds: f64) -> Self {
+        Self { metres, seconds }
+    }
+
+    /// Kilograms in a unit of mass.
+    pub fn kilograms(self) -> f64 {
+        G as f64 * self.metres.powi(3) / (GRAVITATIONAL_CONSTANT * self.seconds.powi(2))
+    }
+
+    pub fn length(self, metres: f64) -> f32 {
+        (metres / self.metres) as f32
+    }
+
+    pub fn mass(self, kilograms: f64) -> f32 {
+        (kilograms / self.kilograms()) as f32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn circular_orbits_keep_their_speed_and_period() {
+        let units = Units::new(ASTRONOMICAL_UNIT / 1E4, 2.0 * DAY);
+        let (sun, distance) = (1.988_47E30, ASTRONOMICAL_UNIT);
+
+        let speed = (GRAVITATIONAL_CONSTANT * sun / distance).sqrt();
+        let simulated = (G * units.mass(sun) / units.length(distance)).sqrt();
+        let physical = simulated as f64 * units.metres / units.seconds;
+        assert!((physical / speed - 1.0).abs() < 1E-5);
+
+        // Two days pass per simulated second.
+        let period = std::f32::consts::TAU * units.length(distance) / simulated;
+        assert!((period - 365.256 / 2.0).abs() < 0.1);
+    }
+}